@@ -0,0 +1,71 @@
+-- 数据库升级脚本，在 demo_rs.sql 基础上按顺序执行
+
+-- 角色继承：上级角色，0 表示无上级
+ALTER TABLE `t_role`
+    ADD COLUMN `parent_id` bigint NOT NULL DEFAULT 0 COMMENT '上级角色ID' AFTER `role_code`;
+
+-- 角色成员有效期：为空表示不限制，到期后由定时任务移除
+ALTER TABLE `t_role_employee`
+    ADD COLUMN `valid_from` bigint NULL DEFAULT NULL COMMENT '生效时间' AFTER `employee_id`,
+    ADD COLUMN `valid_until` bigint NULL DEFAULT NULL COMMENT '失效时间' AFTER `valid_from`,
+    ADD INDEX `idx_valid_until` (`valid_until`);
+
+-- 拼音检索：姓名/部门名称的全拼与首字母，升级后执行 `search rebuild` 补全已有数据
+ALTER TABLE `t_employee`
+    ADD COLUMN `name_pinyin` varchar(255) NOT NULL DEFAULT '' COMMENT '姓名全拼' AFTER `realname`,
+    ADD COLUMN `name_initials` varchar(64) NOT NULL DEFAULT '' COMMENT '姓名拼音首字母' AFTER `name_pinyin`;
+ALTER TABLE `t_department`
+    ADD COLUMN `name_pinyin` varchar(255) NOT NULL DEFAULT '' COMMENT '部门名称全拼' AFTER `department_name`,
+    ADD COLUMN `name_initials` varchar(64) NOT NULL DEFAULT '' COMMENT '部门名称拼音首字母' AFTER `name_pinyin`;
+
+-- 员工离职记录：summary 保存离职时的变更明细（归档的角色、移交的部门等）
+CREATE TABLE `t_employee_offboard` (
+    `offboard_id` bigint NOT NULL AUTO_INCREMENT,
+    `employee_id` bigint NOT NULL COMMENT '员工ID',
+    `resign_date` bigint NOT NULL COMMENT '离职日期',
+    `reason` varchar(500) NOT NULL DEFAULT '' COMMENT '离职原因',
+    `successor_id` bigint NOT NULL DEFAULT 0 COMMENT '部门负责人接任人ID，0 表示无',
+    `summary` text NOT NULL COMMENT '变更明细（JSON）',
+    `operator_id` bigint NOT NULL COMMENT '操作人ID',
+    `create_time` bigint NOT NULL,
+    PRIMARY KEY (`offboard_id`),
+    KEY `idx_employee_id` (`employee_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='员工离职记录';
+
+-- 员工任职履历：每次部门或职位变动记录一条，effective_time 起生效
+CREATE TABLE `t_employee_history` (
+    `history_id` bigint NOT NULL AUTO_INCREMENT,
+    `employee_id` bigint NOT NULL COMMENT '员工ID',
+    `change_type` varchar(20) NOT NULL COMMENT '变动类型：hire 入职、transfer 调动部门、position 调整职位、restore 恢复、leave 离职或删除',
+    `prev_department_id` bigint NOT NULL DEFAULT 0 COMMENT '变动前部门ID',
+    `prev_position_id` bigint NOT NULL DEFAULT 0 COMMENT '变动前职位ID',
+    `department_id` bigint NOT NULL COMMENT '变动后部门ID',
+    `position_id` bigint NOT NULL COMMENT '变动后职位ID',
+    `effective_time` bigint NOT NULL COMMENT '生效时间',
+    `operator_id` bigint NOT NULL DEFAULT 0 COMMENT '操作人ID，0 表示系统',
+    `reason` varchar(500) NOT NULL DEFAULT '' COMMENT '变动原因',
+    `create_time` bigint NOT NULL,
+    PRIMARY KEY (`history_id`),
+    KEY `idx_employee_id` (`employee_id`, `effective_time`),
+    KEY `idx_department_id` (`department_id`, `effective_time`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='员工任职履历';
+
+-- 在职员工以创建时间补一条入职记录，已删除员工的离开时间未知，不补录
+INSERT INTO `t_employee_history`
+    (`employee_id`, `change_type`, `department_id`, `position_id`, `effective_time`, `reason`, `create_time`)
+SELECT `employee_id`, 'hire', `department_id`, `position_id`, `create_time`, '历史数据初始化', UNIX_TIMESTAMP()
+FROM `t_employee`
+WHERE `deleted_flag` = 0;
+
+-- 部门层级索引：path 为从顶级部门到自身的ID路径（如 /1/5/12/），depth 从 0 开始
+-- 升级后执行 `department rebuild-path` 生成已有部门的路径
+ALTER TABLE `t_department`
+    ADD COLUMN `path` varchar(1000) NOT NULL DEFAULT '' COMMENT '层级路径' AFTER `parent_id`,
+    ADD COLUMN `depth` int NOT NULL DEFAULT 0 COMMENT '层级深度，顶级部门为 0' AFTER `path`,
+    ADD INDEX `idx_path` (`path`(255));
+
+-- 删除标记统一为 tinyint unsigned（0 正常，1 已删除），与 t_employee 一致
+ALTER TABLE `t_position`
+    MODIFY COLUMN `deleted_flag` tinyint unsigned NOT NULL DEFAULT 0 COMMENT '删除标记：0 正常，1 已删除';
+ALTER TABLE `t_menu`
+    MODIFY COLUMN `deleted_flag` tinyint unsigned NOT NULL DEFAULT 0 COMMENT '删除标记：0 正常，1 已删除';
@@ -1,5 +1,7 @@
 
 use serde::{Deserialize, Serialize};
+use crate::common::search::MatchKind;
+use crate::common::tree::TreeItem;
 use validator::Validate;
 
 
@@ -13,20 +15,13 @@ pub struct ReqCreate {
     pub parentid: i64,
 }
 
-// 封装部门下拉列表数据对象
-#[derive(Debug, Validate, Deserialize, Serialize)]
-pub struct RespSelect {
-    pub department_id: i64,
-    pub department_name: String,
-    pub parent_id: i64,
-}
-
 // 封装返回数据对象
 #[derive(Debug, Validate, Deserialize, Serialize)]
 pub struct RespInfo{
     pub department_id: i64,
     pub department_name: String,
     pub manager_id: i64,
+    pub manager_name: String,
     pub parent_id: i64,
     pub sort: i32,
     pub create_time: i64,
@@ -51,4 +46,99 @@ pub struct UpdateInfo {
     pub parentid: i64,
     pub create_time: i64,
     pub create_time_str: String,
-}
\ No newline at end of file
+}
+
+/** 部门搜索结果，按 score 降序 */
+#[derive(Debug, Serialize)]
+pub struct RespSearch {
+    pub department_id: i64,
+    pub department_name: String,
+    pub parent_id: i64,
+    pub kind: MatchKind,
+    pub score: u32,
+    pub highlight: String,
+}
+
+/** 移动部门：调整上级部门并放到新上级下的指定位置 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqMove {
+    pub department_id: i64,
+    /// 新的上级部门ID，0 表示顶级部门
+    pub parent_id: i64,
+    /// 在新上级的下级部门中的位置（从 0 开始），默认放到最后
+    pub index: Option<usize>,
+}
+
+/** 同级部门排序：按给定顺序重写 sort */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqReorder {
+    pub parent_id: i64,
+    /// 该上级部门下的全部下级部门ID，按新顺序排列
+    #[validate(length(min = 1, message = "部门列表不能为空"))]
+    pub department_ids: Vec<i64>,
+}
+
+/** 上级链或下级部门中的节点 */
+#[derive(Debug, Serialize)]
+pub struct RespPathNode {
+    pub department_id: i64,
+    pub department_name: String,
+    pub parent_id: i64,
+    pub depth: i32,
+}
+
+/** 汇报关系中的员工，manager_id 为其直属上级 */
+#[derive(Debug, Clone, Serialize)]
+pub struct RespReport {
+    pub employee_id: i64,
+    pub realname: String,
+    pub department_id: i64,
+    pub department_name: String,
+    pub manager_id: i64,
+}
+
+impl TreeItem for RespReport {
+    fn id(&self) -> i64 {
+        self.employee_id
+    }
+
+    fn parent_id(&self) -> i64 {
+        self.manager_id
+    }
+}
+
+/** 合并部门：将源部门的下级部门与员工移入目标部门后删除源部门 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqMerge {
+    pub source_id: i64,
+    pub target_id: i64,
+    /// 只预览将要移动的内容，不写入
+    #[serde(default)]
+    pub preview: bool,
+    /// 员工调动的生效日期，格式 2006-01-02，默认当前时间
+    pub effective_date: Option<String>,
+    #[validate(length(max = 500, message = "合并原因不能超过500个字符"))]
+    pub reason: Option<String>,
+}
+
+/** 合并时移动的员工 */
+#[derive(Debug, Serialize)]
+pub struct RespMergeEmployee {
+    pub employee_id: i64,
+    pub realname: String,
+}
+
+/** 合并结果或预览 */
+#[derive(Debug, Serialize)]
+pub struct RespMerge {
+    pub preview: bool,
+    pub source_id: i64,
+    pub source_name: String,
+    pub target_id: i64,
+    pub target_name: String,
+    /// 移入目标部门的直接下级部门（连同其子部门）
+    pub children: Vec<RespPathNode>,
+    pub employees: Vec<RespMergeEmployee>,
+    /// 仍指向源部门的已删除员工，所属部门一并改为目标部门，不记录履历
+    pub deleted_employees: Vec<RespMergeEmployee>,
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use crate::common::search::MatchKind;
+use validator::{Validate, ValidationError};
 
 
 
@@ -28,6 +29,7 @@ pub struct ReqCreate{
 pub struct RespInfo{
     pub employee_id:i64,
     pub realname:String,
+    pub avatar:String,
     pub phone:String,
     pub department_id:i64,
     pub login_name: String,
@@ -35,6 +37,9 @@ pub struct RespInfo{
     pub gender:u8,
     pub disabled_flag:u8,
     pub position_id:i64,
+    pub department_name:String,
+    pub position_name:String,
+    pub role_names:Vec<String>,
     pub create_time:i64,
     pub create_time_str:String,
 }
@@ -63,6 +68,11 @@ pub struct UpdateInfo{
     pub gender:u8,
     pub disabled_flag:u8,
     pub position_id:i64,
+    /// 部门或职位变动的生效日期，格式 2006-01-02，默认当前时间
+    pub effective_date: Option<String>,
+    /// 部门或职位变动的原因
+    #[validate(length(max = 500, message = "变动原因不能超过500个字符"))]
+    pub reason: Option<String>,
 
 }
 
@@ -88,3 +98,209 @@ pub struct RespSelectOption {
     pub department_id: i64,
     pub department_name: String,
 }
+
+
+/** 导入行，字段为表格中的原始文本，row 为表格行号（表头为第 1 行） */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportRow {
+    pub row: usize,
+    pub realname: String,
+    pub phone: String,
+    pub login_name: String,
+    pub email: String,
+    pub gender: String,
+    pub department: String,
+    pub position: String,
+    pub disabled: String,
+}
+
+/** 导入失败的行及原因 */
+#[derive(Debug, Serialize)]
+pub struct RespImportError {
+    #[serde(flatten)]
+    pub data: ImportRow,
+    pub errors: Vec<String>,
+}
+
+/** 导入结果，strict 模式下存在错误行时不写入任何数据 */
+#[derive(Debug, Serialize)]
+pub struct RespImportReport {
+    pub dry_run: bool,
+    pub strict: bool,
+    pub total: usize,
+    pub valid: usize,
+    pub created: usize,
+    pub failed: Vec<RespImportError>,
+}
+
+
+/** 导出行，部门与职位已解析为名称 */
+#[derive(Debug, Serialize)]
+pub struct RespExportRow {
+    pub employee_id: i64,
+    pub realname: String,
+    pub phone: String,
+    pub login_name: String,
+    pub email: String,
+    pub gender: u8,
+    pub department_name: String,
+    pub position_name: String,
+    pub disabled_flag: u8,
+    pub create_time_str: String,
+}
+
+
+/** 头像上传结果 */
+#[derive(Debug, Serialize)]
+pub struct RespAvatar {
+    pub avatar: String,
+    pub thumbnail: String,
+}
+
+
+/** 当前登录员工的个人资料 */
+#[derive(Debug, Serialize)]
+pub struct RespProfile {
+    pub employee_id: i64,
+    pub login_name: String,
+    pub realname: String,
+    pub avatar: String,
+    pub gender: u8,
+    pub phone: String,
+    pub email: String,
+    pub department_id: i64,
+    pub department_name: String,
+    pub position_id: i64,
+    pub position_name: String,
+    pub administrator_flag: i8,
+    pub remark: String,
+    pub create_time: i64,
+    pub create_time_str: String,
+}
+
+/** 个人资料修改对象，仅允许修改以下字段，未传的字段保持不变 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReqProfile {
+    /// 只能是通过上传接口生成的本人头像地址，空字符串表示清除头像
+    pub avatar: Option<String>,
+    #[validate(email(message = "邮箱格式错误"))]
+    pub email: Option<String>,
+    #[validate(custom(function = "validate_phone", message = "手机号码格式错误"))]
+    pub phone: Option<String>,
+    #[validate(length(max = 255, message = "备注不能超过255个字符"))]
+    pub remark: Option<String>,
+}
+
+/** 员工搜索结果，按 score 降序 */
+#[derive(Debug, Serialize)]
+pub struct RespSearch {
+    pub employee_id: i64,
+    pub realname: String,
+    pub login_name: String,
+    pub phone: String,
+    pub email: String,
+    pub department_id: i64,
+    pub department_name: String,
+    /// 命中的字段：realname、login_name、phone、email
+    pub field: String,
+    pub kind: MatchKind,
+    pub score: u32,
+    pub highlight: String,
+}
+
+/** 员工离职对象 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqOffboard {
+    pub employee_id: i64,
+    /// 离职日期，格式 2006-01-02
+    #[validate(length(equal = 10, message = "离职日期格式错误"))]
+    pub resign_date: String,
+    #[validate(length(min = 1, max = 500, message = "离职原因必填，且不能超过500个字符"))]
+    pub reason: String,
+    /// 员工担任部门负责人时必填，由其接任
+    pub successor_id: Option<i64>,
+}
+
+/** 离职时归档并移除的角色 */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RespOffboardRole {
+    pub role_id: i64,
+    pub role_name: String,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+}
+
+/** 离职时移交负责人的部门 */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RespOffboardDepartment {
+    pub department_id: i64,
+    pub department_name: String,
+}
+
+/** 离职办理结果，同时作为离职记录保存 */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RespOffboard {
+    pub offboard_id: i64,
+    pub employee_id: i64,
+    pub realname: String,
+    pub resign_date: String,
+    pub reason: String,
+    /// 账号是否由启用变为禁用
+    pub disabled: bool,
+    /// 是否清除了登录会话
+    pub sessions_revoked: bool,
+    pub archived_roles: Vec<RespOffboardRole>,
+    pub reassigned_departments: Vec<RespOffboardDepartment>,
+    pub successor_id: i64,
+    pub successor_name: String,
+    pub operator_id: i64,
+    pub create_time: i64,
+    pub create_time_str: String,
+}
+
+/** 任职履历 */
+#[derive(Debug, Serialize)]
+pub struct RespHistory {
+    pub history_id: i64,
+    /// hire 入职、transfer 调动部门、position 调整职位、restore 恢复
+    pub change_type: String,
+    pub prev_department_id: i64,
+    pub prev_department_name: String,
+    pub prev_position_id: i64,
+    pub prev_position_name: String,
+    pub department_id: i64,
+    pub department_name: String,
+    pub position_id: i64,
+    pub position_name: String,
+    pub effective_time: i64,
+    pub effective_time_str: String,
+    pub operator_id: i64,
+    pub operator_name: String,
+    pub reason: String,
+    pub create_time: i64,
+    pub create_time_str: String,
+}
+
+/** 某一时间点在部门任职的员工 */
+#[derive(Debug, Serialize)]
+pub struct RespHistoryMember {
+    pub employee_id: i64,
+    pub realname: String,
+    pub department_id: i64,
+    pub department_name: String,
+    pub position_id: i64,
+    pub position_name: String,
+    /// 进入该部门（或最近一次变动）的生效时间
+    pub since: i64,
+    pub since_str: String,
+}
+
+/// 手机号码：1 开头的 11 位数字
+pub fn validate_phone(phone: &str) -> Result<(), ValidationError> {
+    if phone.len() == 11 && phone.starts_with('1') && phone.bytes().all(|b| b.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("phone"))
+    }
+}
@@ -1,5 +1,6 @@
 use validator::Validate;
 use serde::{Deserialize, Serialize};
+use crate::common::tree::{TreeItem, TreeNode};
 
 /** 封装输入参数 */
 #[derive(Debug, Validate, Deserialize, Serialize)]
@@ -15,6 +16,61 @@ pub struct ReqLogin {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RespLogin {
     pub name: String,
-    pub role: i64,
+    pub roles: Vec<i64>,
     pub auth_token: String,
-}
\ No newline at end of file
+}
+
+/** 当前登录员工信息 */
+#[derive(Debug, Serialize)]
+pub struct RespCurrentUser {
+    pub employee_id: i64,
+    pub login_name: String,
+    pub realname: String,
+    pub avatar: String,
+    pub phone: String,
+    pub email: String,
+    pub department_id: i64,
+    pub position_id: i64,
+    pub administrator_flag: i8,
+    pub roles: Vec<RespUserRole>,
+    pub menus: Vec<TreeNode<RespMenuRoute>>,
+    pub web_perms: Vec<String>,
+}
+
+/** 当前登录员工的角色 */
+#[derive(Debug, Serialize)]
+pub struct RespUserRole {
+    pub role_id: i64,
+    pub role_name: String,
+    pub role_code: String,
+}
+
+/** 前端菜单路由 */
+#[derive(Debug, Clone, Serialize)]
+pub struct RespMenuRoute {
+    pub menu_id: i64,
+    pub menu_name: String,
+    pub menu_type: i32,
+    pub parent_id: i64,
+    pub path: Option<String>,
+    pub component: Option<String>,
+    pub icon: Option<String>,
+    pub frame_flag: i8,
+    pub frame_url: Option<String>,
+    pub cache_flag: i8,
+    pub sort: Option<i32>,
+}
+
+impl TreeItem for RespMenuRoute {
+    fn id(&self) -> i64 {
+        self.menu_id
+    }
+
+    fn parent_id(&self) -> i64 {
+        self.parent_id
+    }
+
+    fn sort_key(&self) -> i64 {
+        self.sort.unwrap_or_default() as i64
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// 菜单导出文档版本
+pub const MENU_DOCUMENT_VERSION: u32 = 1;
+
+/** 菜单导入导出文档：使用菜单名称路径作为稳定的 key，不依赖自增ID */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct MenuDocument {
+    pub version: u32,
+    pub menus: Vec<MenuEntry>,
+    #[serde(default)]
+    pub role_menus: Vec<RoleMenuEntry>,
+}
+
+/** 菜单条目 */
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MenuEntry {
+    pub key: String,
+    pub parent_key: Option<String>,
+    pub menu_name: String,
+    pub menu_type: i32,
+    pub sort: Option<i32>,
+    pub path: Option<String>,
+    pub component: Option<String>,
+    pub perms_type: Option<i32>,
+    pub api_perms: Option<String>,
+    pub web_perms: Option<String>,
+    pub icon: Option<String>,
+    pub context_menu_key: Option<String>,
+    pub frame_flag: i8,
+    pub frame_url: Option<String>,
+    pub cache_flag: i8,
+    pub visible_flag: i8,
+    pub disabled_flag: i8,
+}
+
+/** 角色功能绑定，按角色编码关联 */
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RoleMenuEntry {
+    pub role_code: String,
+    pub menu_keys: Vec<String>,
+}
+
+/** 导入结果 */
+#[derive(Debug, Default, Serialize)]
+pub struct RespImportReport {
+    pub dry_run: bool,
+    pub created: Vec<String>,
+    pub updated: Vec<MenuChange>,
+    pub unchanged: usize,
+    pub role_menus: Vec<RoleMenuChange>,
+    pub missing_roles: Vec<String>,
+}
+
+/** 菜单变更的字段 */
+#[derive(Debug, Serialize)]
+pub struct MenuChange {
+    pub key: String,
+    pub fields: Vec<&'static str>,
+}
+
+/** 角色功能绑定的变更 */
+#[derive(Debug, Serialize)]
+pub struct RoleMenuChange {
+    pub role_code: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
@@ -2,4 +2,5 @@ pub mod department_dto;
 pub mod login_dto;
 pub mod role_dto;
 pub mod position_dto;
-pub mod employee_dto;
\ No newline at end of file
+pub mod employee_dto;
+pub mod menu_dto;
\ No newline at end of file
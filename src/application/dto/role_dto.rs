@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::common::tree::{TreeItem, TreeNode};
 
 
 
@@ -13,6 +14,8 @@ pub struct ReqCreate {
     pub rolename: String,
     #[validate(length(min = 1, message = "角色编码必填"))]
     pub rolecode: String,
+    #[serde(default)]
+    pub parentid: i64,
     pub remark: String,
 }
 
@@ -22,6 +25,7 @@ pub struct RespInfo {
     pub roleid: i64,
     pub rolename: String,
     pub rolecode: String,
+    pub parentid: i64,
     pub remark: String,
     pub create_time: i64,
     pub create_time_str: String,
@@ -44,6 +48,8 @@ pub struct UpdateInfo {
     pub rolename: String,
     #[validate(length(min = 1, message = "角色编码必填"))]
     pub rolecode: String,
+    /// 上级角色，不传时保持原上级，0 表示顶级角色
+    pub parentid: Option<i64>,
     pub remark: String,
     pub create_time: i64,
     pub create_time_str: String,
@@ -68,6 +74,10 @@ pub struct RespEmpInfo{
     pub gender:u8,
     pub disabled_flag:u8,
     pub position_id:i64,
+    pub valid_from:Option<i64>,
+    pub valid_until:Option<i64>,
+    pub valid_until_str:String,
+    pub expiring:bool,
     pub create_time:i64,
     pub create_time_str:String,
 }
@@ -81,18 +91,158 @@ pub struct RespEmpList {
 }
 
 
-/** 返回列表数据对象 */
 #[derive(Debug, Serialize)]
-pub struct RespMenuSelect {
+pub struct RespRoleMenu {
+    pub role_id: i64,
+    pub menu_id: i64,
+}
+
+
+/** 角色的有效功能权限，source_role_id 为授权来源角色 */
+#[derive(Debug, Serialize)]
+pub struct RespEffectiveMenu {
+    pub menu_id: i64,
+    pub source_role_id: i64,
+    pub inherited: bool,
+}
+
+/** 封装角色功能权限保存对象 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqRoleMenu {
+    pub roleid: i64,
+    pub menu_ids: Vec<i64>,
+}
+
+
+/** 封装角色成员批量添加/移除对象，有效期为空表示不限制 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqRoleEmp {
+    pub roleid: i64,
+    #[validate(length(min = 1, message = "员工必选"))]
+    pub employee_ids: Vec<i64>,
+    #[serde(default)]
+    pub valid_from: Option<i64>,
+    #[serde(default)]
+    pub valid_until: Option<i64>,
+}
+
+
+/** 封装员工角色整体替换对象 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqEmpRole {
+    pub employee_id: i64,
+    pub role_ids: Vec<i64>,
+}
+
+
+/** 角色成员变更结果 */
+#[derive(Debug, Serialize)]
+pub struct RespRoleEmpChange {
+    pub changed: Vec<i64>,
+    pub skipped: Vec<i64>,
+}
+
+
+/** 封装角色复制对象，名称与编码追加后缀，可选复制成员 */
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct ReqRoleClone {
+    pub roleid: i64,
+    #[serde(default = "default_clone_suffix")]
+    #[validate(length(min = 1, message = "后缀必填"))]
+    pub suffix: String,
+    #[serde(default)]
+    pub with_members: bool,
+}
+
+fn default_clone_suffix() -> String {
+    "_copy".to_string()
+}
+
+
+/** 功能权限对比中功能所属的一方，none 表示仅为展示上级路径 */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareSide {
+    OnlyA,
+    OnlyB,
+    Both,
+    None,
+}
+
+/** 功能权限对比树节点 */
+#[derive(Debug, Clone, Serialize)]
+pub struct RespMenuCompare {
     pub menu_id: i64,
     pub menu_name: String,
+    pub menu_type: i32,
+    pub web_perms: Option<String>,
+    pub side: CompareSide,
+    #[serde(skip)]
     pub parent_id: i64,
+    #[serde(skip)]
+    pub sort: i64,
 }
 
+impl TreeItem for RespMenuCompare {
+    fn id(&self) -> i64 {
+        self.menu_id
+    }
 
+    fn parent_id(&self) -> i64 {
+        self.parent_id
+    }
 
+    fn sort_key(&self) -> i64 {
+        self.sort
+    }
+}
+
+/** 两个角色的有效功能权限对比 */
 #[derive(Debug, Serialize)]
-pub struct RespRoleMenu {
-    pub role_id: i64,
+pub struct RespRoleCompare {
+    pub role_a: RespSelect,
+    pub role_b: RespSelect,
+    pub only_a: Vec<i64>,
+    pub only_b: Vec<i64>,
+    pub both: Vec<i64>,
+    pub tree: Vec<TreeNode<RespMenuCompare>>,
+}
+
+
+/** 权限矩阵单元格：角色对功能的授权方式 */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantKind {
+    None,
+    Direct,
+    Inherited,
+}
+
+/** 权限矩阵行：功能及其在各角色下的授权，grants 与角色列一一对应 */
+#[derive(Debug, Serialize)]
+pub struct RespMatrixMenu {
     pub menu_id: i64,
-}
\ No newline at end of file
+    pub path: String,
+    pub menu_type: i32,
+    pub web_perms: Option<String>,
+    pub grants: Vec<GrantKind>,
+}
+
+/** 员工及其当前生效的角色 */
+#[derive(Debug, Serialize)]
+pub struct RespMatrixEmployee {
+    pub employee_id: i64,
+    pub login_name: String,
+    pub realname: String,
+    pub administrator_flag: i8,
+    pub roles: Vec<String>,
+    pub inherited_roles: Vec<String>,
+}
+
+/** 角色 × 功能权限矩阵 */
+#[derive(Debug, Serialize)]
+pub struct RespRoleMatrix {
+    pub roles: Vec<RespSelect>,
+    pub menus: Vec<RespMatrixMenu>,
+    pub employees: Vec<RespMatrixEmployee>,
+}
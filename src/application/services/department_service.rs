@@ -2,10 +2,13 @@ use std::collections::HashMap;
 use tracing;
 
 use crate::infrastructure::repository::department_repository::DepartmentRepository;
-use crate::application::dto::department_dto::{ReqCreate, UpdateInfo, RespInfo, RespList};
+use crate::application::dto::department_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSearch,
+    ReqMove, ReqReorder, RespPathNode, RespReport,
+    ReqMerge, RespMerge};
 use crate::common::{
-    result::response::{ApiOK, Result},
+    result::response::{ApiErr, ApiFile, ApiOK, Result},
     tree,
+    orgchart::{self, ChartFormat, OrgUnit},
 };
 
 use std::sync::Arc;
@@ -27,7 +30,7 @@ impl DepartmentService {
         self.repository.create(req).await
     }
 
-    pub async fn select_list(&self) -> Result<ApiOK<Vec<tree::TreeNode>>> {
+    pub async fn select_list(&self) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
         tracing::info!("Fetching department tree");
         self.repository.select_list().await
     }
@@ -51,4 +54,84 @@ impl DepartmentService {
         tracing::info!("Deleting department: {}", department_id);
         self.repository.delete(department_id).await
     }
+
+    pub async fn search(&self, query: HashMap<String, String>) -> Result<ApiOK<Vec<RespSearch>>> {
+        tracing::info!("Searching departments with query: {:?}", query);
+        self.repository.search(query).await
+    }
+
+    pub async fn rebuild_pinyin(&self) -> Result<u64> {
+        tracing::info!("Rebuilding department pinyin keys");
+        self.repository.rebuild_pinyin().await
+    }
+
+    pub async fn move_department(&self, req: ReqMove) -> Result<ApiOK<()>> {
+        tracing::info!("Moving department ID: {} to parent ID: {}", req.department_id, req.parent_id);
+        self.repository.move_department(req).await
+    }
+
+    pub async fn reorder(&self, req: ReqReorder) -> Result<ApiOK<()>> {
+        tracing::info!("Reordering departments under parent ID: {}", req.parent_id);
+        self.repository.reorder(req).await
+    }
+
+    pub async fn ancestors(&self, department_id: i64) -> Result<ApiOK<Vec<RespPathNode>>> {
+        tracing::info!("Fetching ancestors of department ID: {}", department_id);
+        self.repository.ancestors(department_id).await
+    }
+
+    pub async fn descendants(&self, department_id: i64, depth: Option<i32>) -> Result<ApiOK<Vec<RespPathNode>>> {
+        tracing::info!("Fetching descendants of department ID: {}", department_id);
+        self.repository.descendants(department_id, depth).await
+    }
+
+    pub async fn rebuild_path(&self) -> Result<u64> {
+        tracing::info!("Rebuilding department paths");
+        self.repository.rebuild_path().await
+    }
+
+    pub async fn managed(&self, employee_id: i64) -> Result<ApiOK<Vec<RespInfo>>> {
+        tracing::info!("Fetching departments managed by employee ID: {}", employee_id);
+        self.repository.managed(employee_id).await
+    }
+
+    pub async fn direct_reports(&self, employee_id: i64) -> Result<ApiOK<Vec<RespReport>>> {
+        tracing::info!("Fetching direct reports of employee ID: {}", employee_id);
+        self.repository.direct_reports(employee_id).await
+    }
+
+    pub async fn reporting_tree(&self, employee_id: i64) -> Result<ApiOK<Vec<tree::TreeNode<RespReport>>>> {
+        tracing::info!("Fetching reporting tree of employee ID: {}", employee_id);
+        self.repository.reporting_tree(employee_id).await
+    }
+
+    // 组织架构图：root 为根部门，depth 为保留的层数，employees 时附带员工姓名
+    pub async fn org_chart(&self, root: Option<i64>, depth: Option<usize>, employees: bool) -> Result<Vec<tree::TreeNode<OrgUnit>>> {
+        tracing::info!("Building org chart, root: {:?}, depth: {:?}", root, depth);
+        let units = self.repository.org_units(employees).await?;
+        orgchart::build(units, root, depth).map_err(|e| ApiErr::ErrNotFound(Some(e.to_string())))
+    }
+
+    // 组织架构图导出文件
+    pub async fn org_chart_file(&self, format: ChartFormat, root: Option<i64>, depth: Option<usize>, employees: bool) -> Result<ApiFile> {
+        let roots = self.org_chart(root, depth, employees).await?;
+        let data = match format {
+            ChartFormat::Json => serde_json::to_vec_pretty(&roots).map_err(|e| {
+                tracing::error!(error = ?e, "error render org chart");
+                ApiErr::ErrSystem(None)
+            })?,
+            ChartFormat::Dot => orgchart::to_dot(&roots).into_bytes(),
+            ChartFormat::Mermaid => orgchart::to_mermaid(&roots).into_bytes(),
+        };
+        Ok(ApiFile {
+            filename: format!("org_chart.{}", format.extension()),
+            content_type: format.content_type(),
+            data,
+        })
+    }
+
+    pub async fn merge(&self, req: ReqMerge, operator_id: i64) -> Result<ApiOK<RespMerge>> {
+        tracing::info!("Merging department ID: {} into {}, preview: {}", req.source_id, req.target_id, req.preview);
+        self.repository.merge(req, operator_id).await
+    }
 }
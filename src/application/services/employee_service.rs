@@ -2,8 +2,17 @@ use std::sync::Arc;
 use tracing;
 
 use crate::infrastructure::repository::employee_repository::EmployeeRepository;
-use crate::application::dto::employee_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelectOption};
-use crate::common::result::response::{ApiOK, Result};
+use crate::infrastructure::repository::employee_history_repository::{Change, EmployeeHistoryRepository};
+use crate::application::dto::employee_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelectOption,
+    ImportRow, RespImportReport, RespExportRow, RespAvatar, RespProfile, ReqProfile, RespSearch,
+    ReqOffboard, RespOffboard, RespHistory, RespHistoryMember};
+use crate::common::{avatar, config};
+use crate::infrastructure::storage;
+use sha2::{Digest, Sha256};
+use crate::common::export::{self, ExportFormat, Sheet, XlsxWriter};
+use crate::common::result::response::{ApiErr, ApiFile, ApiFileStream, ApiOK, Result};
+use bytes::Bytes;
+use tokio::sync::mpsc;
 
 use std::collections::HashMap;
 
@@ -16,21 +25,44 @@ use std::collections::HashMap;
 
 
 
+// 导入表格的列：表头名称（中文或英文）与是否必填
+const IMPORT_COLUMNS: [(&str, &str, bool); 8] = [
+    ("姓名", "realname", true),
+    ("手机号码", "phone", true),
+    ("登录名", "login_name", true),
+    ("邮箱", "email", true),
+    ("性别", "gender", false),
+    ("部门", "department", true),
+    ("职位", "position", false),
+    ("禁用", "disabled", false),
+];
+
+// 导出时每批查询的员工数量
+const EXPORT_BATCH_SIZE: u64 = 500;
+
+// 导出的列，与导入列兼容，导出文件可修改后重新导入
+const EXPORT_COLUMNS: [&str; 10] = ["员工ID", "姓名", "手机号码", "登录名", "邮箱", "性别", "部门", "职位", "禁用", "创建时间"];
+
+// 头像文件默认大小上限：2MB
+const AVATAR_MAX_SIZE: usize = 2 * 1024 * 1024;
+
 pub struct EmployeeService {
-    repository: Arc<EmployeeRepository>
+    repository: Arc<EmployeeRepository>,
+    history: Arc<EmployeeHistoryRepository>,
 }
 
 impl EmployeeService {
     pub fn new() -> Self {
         Self {
-            repository: Arc::new(EmployeeRepository::new())
+            repository: Arc::new(EmployeeRepository::new()),
+            history: Arc::new(EmployeeHistoryRepository::new()),
         }
     }
 
 
-    pub async fn create(&self, req: ReqCreate) -> Result<ApiOK<()>> {
+    pub async fn create(&self, req: ReqCreate, operator_id: i64) -> Result<ApiOK<()>> {
         tracing::info!("Creating employee: {}", req.login_name);
-        self.repository.create(req).await
+        self.repository.create(req, operator_id).await
     }
 
     pub async fn list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
@@ -43,9 +75,9 @@ impl EmployeeService {
         self.repository.info(employee_id).await
     }
 
-    pub async fn update(&self, req: UpdateInfo) -> Result<ApiOK<()>> {
+    pub async fn update(&self, req: UpdateInfo, operator_id: i64) -> Result<ApiOK<()>> {
         tracing::info!("Updating employee: {}", req.login_name);
-        self.repository.update(req).await
+        self.repository.update(req, operator_id).await
     }
 
     pub async fn reset_password(&self, employee_id: i64) -> Result<ApiOK<()>> {
@@ -58,9 +90,18 @@ impl EmployeeService {
         self.repository.disabled_flag(employee_id, disabled_flag).await
     }
 
-    pub async fn change_department(&self, employee_id: Vec<i64>, department_id: i64) -> Result<ApiOK<()>> {
+    // 调整部门，effective_date 为生效日期（默认当前时间），reason 为调动原因
+    pub async fn change_department(
+        &self,
+        employee_id: Vec<i64>,
+        department_id: i64,
+        operator_id: i64,
+        effective_date: Option<String>,
+        reason: Option<String>,
+    ) -> Result<ApiOK<()>> {
         tracing::info!("Changing department for employees: {:?}, to department ID: {}", employee_id, department_id);
-        self.repository.change_department(employee_id, department_id).await
+        let change = Change::new(operator_id, effective_date.as_deref(), reason)?;
+        self.repository.change_department(employee_id, department_id, change).await
     }
 
     pub async fn employee_select_list(&self) -> Result<ApiOK<Vec<RespSelectOption>>> {
@@ -68,4 +109,298 @@ impl EmployeeService {
         self.repository.employee_select_list().await
     }
 
+    pub async fn deleted_list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
+        tracing::info!("Listing deleted employees with query: {:?}", query);
+        self.repository.deleted_list(query).await
+    }
+
+    pub async fn delete(&self, employee_id: i64, operator_id: i64) -> Result<ApiOK<()>> {
+        tracing::info!("Deleting employee ID: {}", employee_id);
+        self.repository.delete(employee_id, operator_id).await
+    }
+
+    pub async fn history(&self, employee_id: i64) -> Result<ApiOK<Vec<RespHistory>>> {
+        tracing::info!("Fetching employment history for employee ID: {}", employee_id);
+        self.history.timeline(employee_id).await
+    }
+
+    // 某日在部门任职的员工，date 格式 2006-01-02，按当天结束时的状态计算
+    pub async fn history_members(&self, department_id: i64, date: &str, include_sub: bool) -> Result<ApiOK<Vec<RespHistoryMember>>> {
+        tracing::info!("Fetching members of department ID: {} on {}", department_id, date);
+        let time = Change::new(0, Some(date), None)?.effective_time + 86400 - 1;
+        self.history.members(department_id, time, include_sub).await
+    }
+
+    pub async fn offboard(&self, req: ReqOffboard, operator_id: i64) -> Result<ApiOK<RespOffboard>> {
+        tracing::info!("Offboarding employee ID: {}", req.employee_id);
+        self.repository.offboard(req, operator_id).await
+    }
+
+    pub async fn offboard_info(&self, employee_id: i64) -> Result<ApiOK<RespOffboard>> {
+        tracing::info!("Fetching offboard record for employee ID: {}", employee_id);
+        self.repository.offboard_info(employee_id).await
+    }
+
+    pub async fn restore(&self, employee_id: i64, department_id: Option<i64>, operator_id: i64) -> Result<ApiOK<()>> {
+        tracing::info!("Restoring employee ID: {}", employee_id);
+        self.repository.restore(employee_id, department_id, operator_id).await
+    }
+
+    // 从 CSV/Excel 批量导入员工，dry_run 只校验不写入，strict 时任一行有误则全部不写入
+    pub async fn import(&self, format: ExportFormat, data: &[u8], dry_run: bool, strict: bool, operator_id: i64) -> Result<RespImportReport> {
+        let sheet = export::read(format, data).map_err(|e| ApiErr::ErrParams(Some(format!("文件解析失败：{}", e))))?;
+
+        let mut indexes = Vec::with_capacity(IMPORT_COLUMNS.len());
+        let mut missing = Vec::new();
+        for (label, key, required) in IMPORT_COLUMNS {
+            let index = sheet.headers.iter().position(|h| h == label || h == key);
+            if index.is_none() && required {
+                missing.push(label);
+            }
+            indexes.push(index);
+        }
+        if !missing.is_empty() {
+            return Err(ApiErr::ErrParams(Some(format!("缺少列：{}", missing.join(",")))));
+        }
+
+        let rows: Vec<ImportRow> = sheet
+            .rows
+            .into_iter()
+            .enumerate()
+            .filter(|(_, values)| values.iter().any(|v| !v.is_empty()))
+            .map(|(i, values)| {
+                let cell = |col: usize| indexes[col].and_then(|index| values.get(index)).cloned().unwrap_or_default();
+                ImportRow {
+                    row: i + 2,
+                    realname: cell(0),
+                    phone: cell(1),
+                    login_name: cell(2),
+                    email: cell(3),
+                    gender: cell(4),
+                    department: cell(5),
+                    position: cell(6),
+                    disabled: cell(7),
+                }
+            })
+            .collect();
+        if rows.is_empty() {
+            return Err(ApiErr::ErrParams(Some("文件中没有员工数据".to_string())));
+        }
+
+        tracing::info!("Importing {} employees, dry_run: {}, strict: {}", rows.len(), dry_run, strict);
+        self.repository.import(rows, dry_run, strict, operator_id).await
+    }
+
+    // 导入错误报告：保留导入列并追加行号与错误原因，修改后可直接重新导入
+    pub fn import_error_file(&self, report: &RespImportReport, format: ExportFormat) -> Result<ApiFile> {
+        let mut headers = vec!["行号".to_string()];
+        headers.extend(IMPORT_COLUMNS.iter().map(|(label, _, _)| label.to_string()));
+        headers.push("错误原因".to_string());
+
+        let mut sheet = Sheet::new("导入错误", headers);
+        for failed in &report.failed {
+            let row = &failed.data;
+            sheet.rows.push(vec![
+                row.row.to_string(),
+                row.realname.clone(),
+                row.phone.clone(),
+                row.login_name.clone(),
+                row.email.clone(),
+                row.gender.clone(),
+                row.department.clone(),
+                row.position.clone(),
+                row.disabled.clone(),
+                failed.errors.join("；"),
+            ]);
+        }
+
+        let data = match format {
+            ExportFormat::Csv => export::to_csv(&sheet),
+            ExportFormat::Xlsx => export::to_xlsx(std::slice::from_ref(&sheet)),
+        }
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error render import report");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        Ok(ApiFile {
+            filename: format!("employee_import_errors.{}", format.extension()),
+            content_type: format.content_type(),
+            data,
+        })
+    }
+
+    // 按列表的查询条件导出员工，分批查询并写入响应流
+    pub async fn export(&self, query: HashMap<String, String>, format: ExportFormat) -> Result<ApiFileStream> {
+        tracing::info!("Exporting employees with query: {:?}", query);
+        let repository = self.repository.clone();
+        let (tx, rx) = mpsc::channel::<std::io::Result<Bytes>>(4);
+
+        tokio::spawn(async move {
+            let headers: Vec<String> = EXPORT_COLUMNS.iter().map(|h| h.to_string()).collect();
+            let result = match format {
+                ExportFormat::Csv => Self::export_csv(&repository, &query, &headers, &tx).await,
+                ExportFormat::Xlsx => Self::export_xlsx(&repository, &query, &headers, &tx).await,
+            };
+            if let Err(e) = result {
+                tracing::error!(error = ?e, "error export t_employee");
+                let _ = tx.send(Err(std::io::Error::other(e.to_string()))).await;
+            }
+        });
+
+        Ok(ApiFileStream {
+            filename: format!("employees.{}", format.extension()),
+            content_type: format.content_type(),
+            rx,
+        })
+    }
+
+    async fn export_csv(
+        repository: &EmployeeRepository,
+        query: &HashMap<String, String>,
+        headers: &[String],
+        tx: &mpsc::Sender<std::io::Result<Bytes>>,
+    ) -> anyhow::Result<()> {
+        tx.send(Ok(Bytes::from(export::csv_header(headers)?))).await?;
+        let mut after_id = 0;
+        loop {
+            let batch = repository.export_batch(query, after_id, EXPORT_BATCH_SIZE).await?;
+            let Some(last) = batch.last() else { break };
+            after_id = last.employee_id;
+            let rows: Vec<Vec<String>> = batch.into_iter().map(Self::export_row).collect();
+            // 客户端断开时停止导出
+            tx.send(Ok(Bytes::from(export::csv_rows(&rows)?))).await?;
+        }
+        Ok(())
+    }
+
+    async fn export_xlsx(
+        repository: &EmployeeRepository,
+        query: &HashMap<String, String>,
+        headers: &[String],
+        tx: &mpsc::Sender<std::io::Result<Bytes>>,
+    ) -> anyhow::Result<()> {
+        let mut writer = XlsxWriter::new("员工", headers)?;
+        let mut after_id = 0;
+        loop {
+            let batch = repository.export_batch(query, after_id, EXPORT_BATCH_SIZE).await?;
+            let Some(last) = batch.last() else { break };
+            after_id = last.employee_id;
+            let rows: Vec<Vec<String>> = batch.into_iter().map(Self::export_row).collect();
+            writer.append(&rows)?;
+        }
+        tx.send(Ok(Bytes::from(writer.finish()?))).await?;
+        Ok(())
+    }
+
+    fn export_row(row: RespExportRow) -> Vec<String> {
+        let gender = match row.gender {
+            1 => "男",
+            2 => "女",
+            _ => "未知",
+        };
+        vec![
+            row.employee_id.to_string(),
+            row.realname,
+            row.phone,
+            row.login_name,
+            row.email,
+            gender.to_string(),
+            row.department_name,
+            row.position_name,
+            if row.disabled_flag == 1 { "是" } else { "否" }.to_string(),
+            row.create_time_str,
+        ]
+    }
+
+    // 头像文件大小上限，可通过 storage.avatar_max_size 配置
+    pub fn avatar_max_size() -> usize {
+        config::global()
+            .get_int("storage.avatar_max_size")
+            .map(|v| v as usize)
+            .unwrap_or(AVATAR_MAX_SIZE)
+    }
+
+    // 是否为上传接口生成的本人头像地址：{前缀}/avatar/{员工ID}/{16位摘要}_{尺寸}.png
+    fn is_uploaded_avatar(employee_id: i64, avatar: &str) -> bool {
+        let prefix = storage::global().url(&format!("avatar/{}/", employee_id));
+        let suffix = format!("_{}.png", avatar::AVATAR_SIZE);
+        avatar
+            .strip_prefix(&prefix)
+            .and_then(|name| name.strip_suffix(&suffix))
+            .is_some_and(|digest| digest.len() == 16 && digest.bytes().all(|b| b.is_ascii_hexdigit()))
+    }
+
+    // 上传头像：识别图片类型，生成头像与缩略图写入存储，并更新员工头像地址
+    pub async fn upload_avatar(&self, employee_id: i64, data: Bytes) -> Result<ApiOK<RespAvatar>> {
+        tracing::info!("Uploading avatar for employee ID: {}", employee_id);
+        let max_size = Self::avatar_max_size();
+        if data.len() > max_size {
+            return Err(ApiErr::ErrParams(Some(format!("头像文件不能超过 {}KB", max_size / 1024))));
+        }
+        avatar::sniff(&data).map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?;
+
+        // 文件名包含内容摘要，内容不变则地址不变，便于长期缓存
+        let digest = const_hex::encode(Sha256::digest(&data));
+        let resized = tokio::task::spawn_blocking(move || avatar::resize(&data, &[avatar::AVATAR_SIZE, avatar::THUMBNAIL_SIZE]))
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error resize avatar");
+                ApiErr::ErrSystem(None)
+            })?
+            .map_err(|e| ApiErr::ErrParams(Some(format!("图片解析失败：{}", e))))?;
+
+        let urls = tokio::task::spawn_blocking(move || {
+            let storage = storage::global();
+            let mut urls = Vec::with_capacity(resized.len());
+            for (size, buf) in resized {
+                let key = format!("avatar/{}/{}_{}.png", employee_id, &digest[..16], size);
+                storage.put(&key, &buf)?;
+                urls.push(storage.url(&key));
+            }
+            anyhow::Ok(urls)
+        })
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error store avatar");
+            ApiErr::ErrSystem(None)
+        })?
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error store avatar");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        let resp = RespAvatar {
+            avatar: urls[0].clone(),
+            thumbnail: urls[1].clone(),
+        };
+        self.repository.update_avatar(employee_id, resp.avatar.clone()).await?;
+        Ok(ApiOK(Some(resp)))
+    }
+
+    pub async fn profile(&self, employee_id: i64) -> Result<ApiOK<RespProfile>> {
+        tracing::info!("Fetching profile for employee ID: {}", employee_id);
+        self.repository.profile(employee_id).await
+    }
+
+    // 修改个人资料，头像只能是本人上传的头像地址
+    pub async fn update_profile(&self, employee_id: i64, req: ReqProfile) -> Result<ApiOK<()>> {
+        tracing::info!("Updating profile for employee ID: {}", employee_id);
+        if let Some(avatar) = &req.avatar {
+            if !avatar.is_empty() && !Self::is_uploaded_avatar(employee_id, avatar) {
+                return Err(ApiErr::ErrParams(Some("头像地址无效，请先上传头像".to_string())));
+            }
+        }
+        self.repository.update_profile(employee_id, req).await
+    }
+
+    pub async fn search(&self, query: HashMap<String, String>) -> Result<ApiOK<Vec<RespSearch>>> {
+        tracing::info!("Searching employees with query: {:?}", query);
+        self.repository.search(query).await
+    }
+
+    pub async fn rebuild_pinyin(&self) -> Result<u64> {
+        tracing::info!("Rebuilding employee pinyin keys");
+        self.repository.rebuild_pinyin().await
+    }
 }
@@ -0,0 +1,27 @@
+use tracing;
+
+use crate::common::result::response::{ApiErr, Result};
+use crate::infrastructure::storage;
+
+pub struct FileService;
+
+impl FileService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let owned = key.to_string();
+        tokio::task::spawn_blocking(move || storage::global().get(&owned))
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error read file: {}", key);
+                ApiErr::ErrSystem(None)
+            })?
+            .map_err(|e| {
+                tracing::warn!(error = ?e, "error read file: {}", key);
+                ApiErr::ErrNotFound(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("文件不存在".to_string())))
+    }
+}
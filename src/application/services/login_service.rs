@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use crate::application::dto::login_dto::{ReqLogin, RespLogin};
+use crate::application::dto::login_dto::{ReqLogin, RespLogin, RespCurrentUser};
 use crate::infrastructure::repository::login_repository::LoginRepository;
 use crate::infrastructure::security::identity::Identity;
 use tracing;
@@ -27,4 +27,9 @@ impl LoginService {
         tracing::info!("identity request: {}", identity.id());
         self.repository.logout(identity).await
     }
+
+    pub async fn current_user(&self, identity: Identity) -> Result<ApiOK<RespCurrentUser>> {
+        tracing::info!("Fetching current user: {}", identity.id());
+        self.repository.current_user(identity).await
+    }
 }
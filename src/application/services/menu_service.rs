@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use tracing;
+
+use crate::infrastructure::repository::menu_repository::MenuRepository;
+use crate::application::dto::menu_dto::{MenuDocument, RespImportReport};
+use crate::common::result::response::{ApiOK, Result};
+
+pub struct MenuService {
+    repository: Arc<MenuRepository>,
+}
+
+impl MenuService {
+    pub fn new() -> Self {
+        Self {
+            repository: Arc::new(MenuRepository::new())
+        }
+    }
+
+    pub async fn export(&self) -> Result<ApiOK<MenuDocument>> {
+        tracing::info!("Exporting menus");
+        self.repository.export().await
+    }
+
+    pub async fn import(&self, doc: MenuDocument, operator_id: i64, dry_run: bool) -> Result<ApiOK<RespImportReport>> {
+        tracing::info!("Importing {} menus, dry_run: {}", doc.menus.len(), dry_run);
+        self.repository.import(doc, operator_id, dry_run).await
+    }
+}
@@ -3,3 +3,5 @@ pub mod login_service;
 pub mod role_service;
 pub mod position_service;
 pub mod employee_service;
+pub mod menu_service;
+pub mod file_service;
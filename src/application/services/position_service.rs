@@ -29,7 +29,12 @@ impl PositionService {
 
     pub async fn list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
         tracing::info!("Listing positions with query: {:?}", query);
-        self.repository.list(query).await
+        self.repository.list(query, 0).await
+    }
+
+    pub async fn deleted_list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
+        tracing::info!("Listing deleted positions with query: {:?}", query);
+        self.repository.list(query, 1).await
     }
 
     pub async fn info(&self, postid: u64) -> Result<ApiOK<RespInfo>> {
@@ -50,6 +55,11 @@ impl PositionService {
     pub async fn delete(&self, postid: u64) -> Result<ApiOK<()>> {
         tracing::info!("Deleting position: {}", postid);
         self.repository.delete(postid).await
-    }   
+    }
+
+    pub async fn restore(&self, postid: u64) -> Result<ApiOK<()>> {
+        tracing::info!("Restoring position: {}", postid);
+        self.repository.restore(postid).await
+    }
 
 }
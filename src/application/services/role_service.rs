@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 use tracing;
 
-use crate::application::dto::role_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelect, RespEmpList, RespRoleMenu};
+use crate::application::dto::role_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelect, RespEmpList, RespRoleMenu, ReqRoleMenu,
+    ReqRoleEmp, ReqEmpRole, RespRoleEmpChange, RespEffectiveMenu,
+    ReqRoleClone, RespRoleCompare, GrantKind};
 use crate::common::{
-    result::response::{ApiOK, Result},
+    export::{self, ExportFormat, Sheet},
+    result::response::{ApiErr, ApiFile, ApiOK, Result},
     tree,
 };
 
@@ -58,7 +61,7 @@ impl RoleService {
         self.repository.role_emp_list(query).await
     }
 
-    pub async fn menu_list(&self) -> Result<ApiOK<Vec<tree::TreeNode>>> {
+    pub async fn menu_list(&self) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
         tracing::info!("Fetching menu list");
         self.repository.menu_list().await
     }
@@ -67,4 +70,125 @@ impl RoleService {
         tracing::info!("Fetching role menu: {}", roleid);
         self.repository.role_menu(roleid).await
     }
+
+    pub async fn save_role_menu(&self, req: ReqRoleMenu) -> Result<ApiOK<Vec<RespRoleMenu>>> {
+        tracing::info!("Saving role menu: {}, menus: {:?}", req.roleid, req.menu_ids);
+        self.repository.save_role_menu(req).await
+    }
+
+    pub async fn add_role_emp(&self, req: ReqRoleEmp) -> Result<ApiOK<RespRoleEmpChange>> {
+        tracing::info!("Adding employees {:?} to role: {}", req.employee_ids, req.roleid);
+        self.repository.add_role_emp(req).await
+    }
+
+    pub async fn remove_role_emp(&self, req: ReqRoleEmp) -> Result<ApiOK<RespRoleEmpChange>> {
+        tracing::info!("Removing employees {:?} from role: {}", req.employee_ids, req.roleid);
+        self.repository.remove_role_emp(req).await
+    }
+
+    pub async fn emp_roles(&self, employee_id: i64) -> Result<ApiOK<Vec<RespSelect>>> {
+        tracing::info!("Fetching roles of employee: {}", employee_id);
+        self.repository.emp_roles(employee_id).await
+    }
+
+    pub async fn save_emp_roles(&self, req: ReqEmpRole) -> Result<ApiOK<Vec<RespSelect>>> {
+        tracing::info!("Saving roles {:?} of employee: {}", req.role_ids, req.employee_id);
+        self.repository.save_emp_roles(req).await
+    }
+
+    pub async fn role_tree(&self) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
+        tracing::info!("Fetching role tree");
+        self.repository.role_tree().await
+    }
+
+    pub async fn role_menu_effective(&self, roleid: i64) -> Result<ApiOK<Vec<RespEffectiveMenu>>> {
+        tracing::info!("Fetching effective role menu: {}", roleid);
+        self.repository.role_menu_effective(roleid).await
+    }
+
+    pub async fn set_role_emp_validity(&self, req: ReqRoleEmp) -> Result<ApiOK<RespRoleEmpChange>> {
+        tracing::info!("Updating role member validity: {}", req.roleid);
+        self.repository.set_role_emp_validity(req).await
+    }
+
+    pub async fn sweep_role_emp(&self, since: i64, now: i64) -> Result<usize> {
+        self.repository.sweep_role_emp(since, now).await
+    }
+
+    pub async fn clone_role(&self, req: ReqRoleClone) -> Result<ApiOK<RespInfo>> {
+        tracing::info!("Cloning role: {}", req.roleid);
+        self.repository.clone_role(req).await
+    }
+
+    pub async fn compare_roles(&self, role_a: i64, role_b: i64) -> Result<ApiOK<RespRoleCompare>> {
+        tracing::info!("Comparing roles: {} / {}", role_a, role_b);
+        self.repository.compare_roles(role_a, role_b).await
+    }
+
+    // 权限矩阵：第一个表为角色 × 功能，第二个表为员工及其生效角色
+    pub async fn matrix_sheets(&self) -> Result<Vec<Sheet>> {
+        tracing::info!("Building role permission matrix");
+        let matrix = self.repository.permission_matrix().await?;
+
+        let mut headers = vec!["功能ID".to_string(), "功能路径".to_string(), "类型".to_string(), "前端权限".to_string()];
+        headers.extend(matrix.roles.iter().map(|role| role.rolename.clone()));
+        let mut menus = Sheet::new("角色权限", headers);
+        for menu in matrix.menus {
+            let menu_type = match menu.menu_type {
+                1 => "目录",
+                2 => "菜单",
+                _ => "功能点",
+            };
+            let mut row = vec![menu.menu_id.to_string(), menu.path, menu_type.to_string(), menu.web_perms.unwrap_or_default()];
+            row.extend(menu.grants.into_iter().map(|grant| {
+                match grant {
+                    GrantKind::Direct => "直接",
+                    GrantKind::Inherited => "继承",
+                    GrantKind::None => "",
+                }
+                .to_string()
+            }));
+            menus.rows.push(row);
+        }
+
+        let headers = ["员工ID", "登录名", "姓名", "超级管理员", "角色", "继承角色"];
+        let mut employees = Sheet::new("员工角色", headers.iter().map(|h| h.to_string()).collect());
+        for employee in matrix.employees {
+            employees.rows.push(vec![
+                employee.employee_id.to_string(),
+                employee.login_name,
+                employee.realname,
+                if employee.administrator_flag == 1 { "是" } else { "否" }.to_string(),
+                employee.roles.join(","),
+                employee.inherited_roles.join(","),
+            ]);
+        }
+
+        Ok(vec![menus, employees])
+    }
+
+    // 导出权限矩阵，CSV 只能包含一个表，sheet=employees 时导出员工角色表
+    pub async fn export_matrix(&self, format: ExportFormat, sheet: Option<&str>) -> Result<ApiFile> {
+        let mut sheets = self.matrix_sheets().await?;
+        let (filename, data) = match format {
+            ExportFormat::Xlsx => ("role_matrix".to_string(), export::to_xlsx(&sheets)),
+            ExportFormat::Csv => {
+                let (name, index) = match sheet {
+                    Some("employees") => ("role_matrix_employees", 1),
+                    _ => ("role_matrix", 0),
+                };
+                (name.to_string(), export::to_csv(&sheets.swap_remove(index)))
+            }
+        };
+        let data = data.map_err(|e| {
+            tracing::error!(error = ?e, "error render role matrix");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        Ok(ApiFile {
+            filename: format!("{}.{}", filename, format.extension()),
+            content_type: format.content_type(),
+            data,
+        })
+    }
 }
@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use crate::application::services::department_service::DepartmentService;
+use crate::common::orgchart::ChartFormat;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DepartmentAction {
+    /// 按 parent_id 重新生成部门层级路径（path/depth）
+    RebuildPath,
+    /// 导出组织架构图（json/dot/mermaid），未指定文件时输出到终端
+    OrgChart {
+        #[arg(long, default_value = "dot")]
+        format: String,
+        /// 根部门ID，默认全部顶级部门
+        #[arg(long)]
+        root: Option<i64>,
+        /// 保留的层数，1 表示只输出根部门
+        #[arg(long)]
+        depth: Option<usize>,
+        /// 附带员工姓名
+        #[arg(long)]
+        employees: bool,
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+}
+
+pub async fn execute(action: DepartmentAction) -> Result<()> {
+    let service = DepartmentService::new();
+    match action {
+        DepartmentAction::RebuildPath => {
+            let updated = service.rebuild_path().await?;
+            println!("已更新 {} 个部门的层级路径", updated);
+        }
+        DepartmentAction::OrgChart { format, root, depth, employees, file } => {
+            let chart = service.org_chart_file(ChartFormat::parse(&format)?, root, depth, employees).await?;
+            match file {
+                Some(file) => {
+                    std::fs::write(&file, chart.data).with_context(|| format!("写入文件失败：{}", file))?;
+                    println!("已导出组织架构图到 {}", file);
+                }
+                None => print!("{}", String::from_utf8_lossy(&chart.data)),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::path::Path;
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use crate::application::services::employee_service::EmployeeService;
+use crate::common::export::ExportFormat;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum EmployeeAction {
+    /// 从 CSV/Excel 批量导入员工，格式由文件扩展名决定
+    Import {
+        #[arg(short, long)]
+        file: String,
+        /// 只校验，不写入数据库
+        #[arg(long)]
+        dry_run: bool,
+        /// 任一行有误则全部不写入
+        #[arg(long)]
+        strict: bool,
+        /// 错误报告输出文件（csv/xlsx），修改后可重新导入
+        #[arg(long)]
+        report: Option<String>,
+    },
+}
+
+pub async fn execute(action: EmployeeAction) -> Result<()> {
+    let service = EmployeeService::new();
+    match action {
+        EmployeeAction::Import { file, dry_run, strict, report } => {
+            let format = ExportFormat::parse(extension(&file))?;
+            let data = std::fs::read(&file).with_context(|| format!("读取文件失败：{}", file))?;
+            let result = service.import(format, &data, dry_run, strict, 0).await?;
+
+            println!(
+                "共 {} 行，校验通过 {} 行，失败 {} 行，已导入 {} 行",
+                result.total,
+                result.valid,
+                result.failed.len(),
+                result.created
+            );
+            for failed in &result.failed {
+                println!("第 {} 行：{}", failed.data.row, failed.errors.join("；"));
+            }
+
+            if let Some(report) = report {
+                let file = service.import_error_file(&result, ExportFormat::parse(extension(&report))?)?;
+                std::fs::write(&report, file.data).with_context(|| format!("写入文件失败：{}", report))?;
+                println!("错误报告已写入 {}", report);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn extension(file: &str) -> &str {
+    Path::new(file).extension().and_then(|ext| ext.to_str()).unwrap_or_default()
+}
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use crate::application::dto::menu_dto::MenuDocument;
+use crate::application::services::menu_service::MenuService;
+use crate::common::result::response::ApiOK;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum MenuAction {
+    /// 导出菜单及角色功能绑定到 JSON 文件
+    Export {
+        #[arg(short, long, default_value = "menus.json")]
+        file: String,
+    },
+    /// 从 JSON 文件导入菜单及角色功能绑定
+    Import {
+        #[arg(short, long, default_value = "menus.json")]
+        file: String,
+        /// 只输出差异，不写入数据库
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn execute(action: MenuAction) -> Result<()> {
+    let service = MenuService::new();
+    match action {
+        MenuAction::Export { file } => {
+            let ApiOK(doc) = service.export().await?;
+            let doc = doc.context("菜单导出结果为空")?;
+            std::fs::write(&file, serde_json::to_string_pretty(&doc)?)
+                .with_context(|| format!("写入文件失败：{}", file))?;
+            println!("已导出 {} 个菜单、{} 个角色绑定到 {}", doc.menus.len(), doc.role_menus.len(), file);
+        }
+        MenuAction::Import { file, dry_run } => {
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("读取文件失败：{}", file))?;
+            let doc: MenuDocument = serde_json::from_str(&content)
+                .with_context(|| format!("解析文件失败：{}", file))?;
+            let ApiOK(report) = service.import(doc, 0, dry_run).await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
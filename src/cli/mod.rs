@@ -3,6 +3,11 @@ use clap::{Parser, Subcommand};
 
 pub mod hello;
 pub mod serve;
+pub mod menu;
+pub mod role;
+pub mod employee;
+pub mod search;
+pub mod department;
 
 
 #[derive(Parser)]
@@ -21,4 +26,29 @@ pub enum Command {
         name: String,
     },
     Serve,
+    /// 菜单导入导出
+    Menu {
+        #[command(subcommand)]
+        action: menu::MenuAction,
+    },
+    /// 角色权限审计
+    Role {
+        #[command(subcommand)]
+        action: role::RoleAction,
+    },
+    /// 员工批量导入
+    Employee {
+        #[command(subcommand)]
+        action: employee::EmployeeAction,
+    },
+    /// 部门层级维护
+    Department {
+        #[command(subcommand)]
+        action: department::DepartmentAction,
+    },
+    /// 搜索索引维护
+    Search {
+        #[command(subcommand)]
+        action: search::SearchAction,
+    },
 }
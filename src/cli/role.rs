@@ -0,0 +1,43 @@
+use std::path::Path;
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use crate::application::services::role_service::RoleService;
+use crate::common::export::{self, ExportFormat};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RoleAction {
+    /// 导出角色 × 功能权限矩阵，格式由文件扩展名决定（csv/xlsx）
+    Matrix {
+        #[arg(short, long, default_value = "role_matrix.xlsx")]
+        file: String,
+    },
+}
+
+pub async fn execute(action: RoleAction) -> Result<()> {
+    let service = RoleService::new();
+    match action {
+        RoleAction::Matrix { file } => {
+            let path = Path::new(&file);
+            let format = ExportFormat::parse(path.extension().and_then(|ext| ext.to_str()).unwrap_or_default())?;
+            let sheets = service.matrix_sheets().await?;
+            match format {
+                ExportFormat::Xlsx => {
+                    std::fs::write(path, export::to_xlsx(&sheets)?)
+                        .with_context(|| format!("写入文件失败：{}", file))?;
+                    println!("已导出权限矩阵到 {}", file);
+                }
+                // CSV 每个表单独一个文件，员工角色表追加 _employees 后缀
+                ExportFormat::Csv => {
+                    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("role_matrix");
+                    let employees = path.with_file_name(format!("{}_employees.csv", stem));
+                    std::fs::write(path, export::to_csv(&sheets[0])?)
+                        .with_context(|| format!("写入文件失败：{}", file))?;
+                    std::fs::write(&employees, export::to_csv(&sheets[1])?)
+                        .with_context(|| format!("写入文件失败：{}", employees.display()))?;
+                    println!("已导出权限矩阵到 {} 和 {}", file, employees.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use clap::Subcommand;
+use crate::application::services::department_service::DepartmentService;
+use crate::application::services::employee_service::EmployeeService;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SearchAction {
+    /// 重新计算员工姓名与部门名称的拼音检索键
+    Rebuild,
+}
+
+pub async fn execute(action: SearchAction) -> Result<()> {
+    match action {
+        SearchAction::Rebuild => {
+            let employees = EmployeeService::new().rebuild_pinyin().await?;
+            let departments = DepartmentService::new().rebuild_pinyin().await?;
+            println!("已更新 {} 名员工、{} 个部门的拼音", employees, departments);
+        }
+    }
+    Ok(())
+}
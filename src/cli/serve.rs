@@ -1,16 +1,47 @@
 use anyhow::Result;
+use std::time::Duration;
+use time::macros::offset;
+use crate::application::services::role_service::RoleService;
+use crate::common::xtime;
 use crate::interface::router;
 use tracing::info;
 
+// 角色成员到期清理间隔
+const ROLE_EMP_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
 pub async fn execute(host: String, port: u16) -> Result<()> {
     info!("Starting server on {}:{}", host, port);
     
     let listener = tokio::net::TcpListener::bind(format!("{}:{}", host, port))
         .await?;
+
+    spawn_role_emp_sweep();
     
     info!("Server listening on {}:{}", host, port);
     axum::serve(listener, router::init())
         .await?;
         
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// 定时清理到期的角色成员
+fn spawn_role_emp_sweep() {
+    tokio::spawn(async move {
+        let service = RoleService::new();
+        let mut interval = tokio::time::interval(ROLE_EMP_SWEEP_INTERVAL);
+        let mut since = xtime::now(offset!(+8)).unix_timestamp();
+        loop {
+            interval.tick().await;
+            let now = xtime::now(offset!(+8)).unix_timestamp();
+            match service.sweep_role_emp(since, now).await {
+                Ok(count) => {
+                    if count > 0 {
+                        info!("Removed {} expired role members", count);
+                    }
+                    since = now;
+                }
+                Err(e) => tracing::error!(error = ?e, "role member sweep failed"),
+            }
+        }
+    });
+}
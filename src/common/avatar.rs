@@ -0,0 +1,75 @@
+use std::io::Cursor;
+use anyhow::{bail, Result};
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+
+/// 头像尺寸
+pub const AVATAR_SIZE: u32 = 256;
+/// 缩略图尺寸
+pub const THUMBNAIL_SIZE: u32 = 64;
+/// 解码时允许的最大宽高，文件很小但声明超大尺寸的图片直接拒绝
+pub const MAX_DIMENSION: u32 = 4096;
+/// 解码时允许分配的最大内存
+const MAX_ALLOC: u64 = 64 * 1024 * 1024;
+
+/// 根据文件内容识别图片格式，仅支持 jpeg/png/gif/webp
+pub fn sniff(data: &[u8]) -> Result<ImageFormat> {
+    match image::guess_format(data) {
+        Ok(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP)) => Ok(format),
+        _ => bail!("不支持的图片格式，仅支持 jpg、png、gif、webp"),
+    }
+}
+
+/// 按尺寸居中裁剪为正方形并缩放，统一输出为 png
+pub fn resize(data: &[u8], sizes: &[u32]) -> Result<Vec<(u32, Vec<u8>)>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_ALLOC);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), sniff(data)?);
+    reader.limits(limits);
+    let img = reader.decode()?;
+
+    sizes
+        .iter()
+        .map(|&size| {
+            let mut buf = Vec::new();
+            img.resize_to_fill(size, size, FilterType::Lanczos3)
+                .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)?;
+            Ok((size, buf))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, GenericImageView};
+
+    #[test]
+    fn test_resize() {
+        let mut data = Vec::new();
+        DynamicImage::new_rgb8(300, 200)
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Jpeg)
+            .unwrap();
+        assert_eq!(sniff(&data).unwrap(), ImageFormat::Jpeg);
+
+        let resized = resize(&data, &[AVATAR_SIZE, THUMBNAIL_SIZE]).unwrap();
+        assert_eq!(resized.len(), 2);
+        for (size, buf) in resized {
+            assert_eq!(sniff(&buf).unwrap(), ImageFormat::Png);
+            assert_eq!(image::load_from_memory(&buf).unwrap().dimensions(), (size, size));
+        }
+
+        assert!(sniff(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_resize_limits() {
+        let mut data = Vec::new();
+        DynamicImage::new_luma8(MAX_DIMENSION + 1, 1)
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        assert!(resize(&data, &[AVATAR_SIZE]).is_err());
+    }
+}
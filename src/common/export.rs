@@ -0,0 +1,219 @@
+//! 表格文件（CSV/Excel）的读写
+use std::io::Cursor;
+use anyhow::{bail, Context, Result};
+use calamine::{open_workbook_from_rs, Reader, Xlsx};
+use rust_xlsxwriter::{Format, Workbook};
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    /// 按名称或文件扩展名解析，忽略大小写
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "xlsx" => Ok(Self::Xlsx),
+            other => bail!("不支持的导出格式：{}", other),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+/// 表格数据，第一行为表头
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Sheet {
+    pub fn new(name: &str, headers: Vec<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            headers,
+            rows: Vec::new(),
+        }
+    }
+}
+
+/// 输出单个表格为 CSV，带 BOM 以便 Excel 正确识别中文
+pub fn to_csv(sheet: &Sheet) -> Result<Vec<u8>> {
+    let mut buf = csv_header(&sheet.headers)?;
+    buf.extend(csv_rows(&sheet.rows)?);
+    Ok(buf)
+}
+
+/// CSV 的 BOM 与表头，用于分批输出
+pub fn csv_header(headers: &[String]) -> Result<Vec<u8>> {
+    let mut buf = b"\xEF\xBB\xBF".to_vec();
+    buf.extend(csv_rows(std::slice::from_ref(&headers.to_vec()))?);
+    Ok(buf)
+}
+
+/// CSV 的数据行，用于分批输出
+pub fn csv_rows(rows: &[Vec<String>]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+/// 分批写入的单表 Excel，数据行写入临时文件，内存占用与行数无关
+pub struct XlsxWriter {
+    workbook: Workbook,
+    row: u32,
+}
+
+impl XlsxWriter {
+    pub fn new(name: &str, headers: &[String]) -> Result<Self> {
+        let mut workbook = Workbook::new();
+        let header_format = Format::new().set_bold();
+        let worksheet = workbook.add_worksheet_with_constant_memory();
+        worksheet.set_name(name)?;
+        for (col, header) in headers.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, header, &header_format)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        Ok(Self { workbook, row: 1 })
+    }
+
+    pub fn append(&mut self, rows: &[Vec<String>]) -> Result<()> {
+        let worksheet = self.workbook.worksheet_from_index(0)?;
+        for values in rows {
+            for (col, value) in values.iter().enumerate() {
+                worksheet.write_string(self.row, col as u16, value)?;
+            }
+            self.row += 1;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        Ok(self.workbook.save_to_buffer()?)
+    }
+}
+
+/// 输出多个表格为 Excel 工作簿，每个表格一个工作表
+pub fn to_xlsx(sheets: &[Sheet]) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+
+    for sheet in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(&sheet.name)?;
+        for (col, header) in sheet.headers.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, header, &header_format)?;
+        }
+        for (row, values) in sheet.rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                worksheet.write_string(row as u32 + 1, col as u16, value)?;
+            }
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        worksheet.autofit();
+    }
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+/// 读取 CSV，首行为表头，自动去除 BOM
+pub fn from_csv(data: &[u8]) -> Result<Sheet> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data);
+    let headers = reader.headers()?.iter().map(|h| h.trim().to_string()).collect();
+
+    let mut sheet = Sheet::new("", headers);
+    for record in reader.records() {
+        sheet.rows.push(record?.iter().map(|v| v.trim().to_string()).collect());
+    }
+    Ok(sheet)
+}
+
+/// 读取 Excel 的第一个工作表，首行为表头
+pub fn from_xlsx(data: &[u8]) -> Result<Sheet> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(data))?;
+    let name = workbook.sheet_names().first().cloned().context("工作簿中没有工作表")?;
+    let range = workbook.worksheet_range(&name)?;
+
+    let mut rows = range.rows().map(|row| row.iter().map(|cell| cell.to_string().trim().to_string()).collect::<Vec<_>>());
+    let mut sheet = Sheet::new(&name, rows.next().unwrap_or_default());
+    sheet.rows.extend(rows);
+    Ok(sheet)
+}
+
+/// 按格式读取表格
+pub fn read(format: ExportFormat, data: &[u8]) -> Result<Sheet> {
+    match format {
+        ExportFormat::Csv => from_csv(data),
+        ExportFormat::Xlsx => from_xlsx(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let mut sheet = Sheet::new("角色", vec!["名称".to_string(), "备注".to_string()]);
+        sheet.rows.push(vec!["管理员".to_string(), "a,b".to_string()]);
+
+        let buf = to_csv(&sheet).unwrap();
+        assert!(buf.starts_with(b"\xEF\xBB\xBF"));
+        assert_eq!(String::from_utf8(buf[3..].to_vec()).unwrap(), "名称,备注\n管理员,\"a,b\"\n");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut sheet = Sheet::new("员工", vec!["姓名".to_string(), "手机号".to_string()]);
+        sheet.rows.push(vec!["张三".to_string(), "13800000000".to_string()]);
+        sheet.rows.push(vec!["李四".to_string(), String::new()]);
+
+        let csv = from_csv(&to_csv(&sheet).unwrap()).unwrap();
+        assert_eq!(csv.headers, sheet.headers);
+        assert_eq!(csv.rows, sheet.rows);
+
+        let xlsx = from_xlsx(&to_xlsx(std::slice::from_ref(&sheet)).unwrap()).unwrap();
+        assert_eq!(xlsx.name, "员工");
+        assert_eq!(xlsx.headers, sheet.headers);
+        assert_eq!(xlsx.rows, sheet.rows);
+    }
+
+    #[test]
+    fn test_xlsx_writer() {
+        let headers = vec!["姓名".to_string()];
+        let mut writer = XlsxWriter::new("员工", &headers).unwrap();
+        writer.append(&[vec!["张三".to_string()]]).unwrap();
+        writer.append(&[vec!["李四".to_string()]]).unwrap();
+
+        let sheet = from_xlsx(&writer.finish().unwrap()).unwrap();
+        assert_eq!(sheet.headers, headers);
+        assert_eq!(sheet.rows, vec![vec!["张三".to_string()], vec!["李四".to_string()]]);
+    }
+
+    #[test]
+    fn test_format_parse() {
+        assert_eq!(ExportFormat::parse("XLSX").unwrap(), ExportFormat::Xlsx);
+        assert_eq!(ExportFormat::parse("csv").unwrap().extension(), "csv");
+        assert!(ExportFormat::parse("pdf").is_err());
+    }
+}
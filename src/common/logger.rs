@@ -105,8 +105,8 @@ mod tests {
 
     #[test]
     fn test_log_config() -> Result<()> {
-        let mut cfg = Config::default();
-        cfg.merge(File::from_str(
+        let cfg = Config::builder()
+            .add_source(File::from_str(
             r#"
             app:
               debug: true
@@ -117,7 +117,8 @@ mod tests {
               json: true
             "#,
             config::FileFormat::Yaml,
-        ))?;
+            ))
+            .build()?;
 
         let log_config = LogConfig::from_config(&cfg)?;
         assert_eq!(log_config.level, Level::DEBUG);
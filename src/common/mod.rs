@@ -2,6 +2,10 @@ pub mod config;
 pub mod logger;
 pub mod xtime;
 pub mod tree;
+pub mod export;
+pub mod avatar;
+pub mod search;
+pub mod orgchart;
 pub mod utils;
 pub mod crypto;
 pub mod result;
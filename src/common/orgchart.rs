@@ -0,0 +1,261 @@
+//! 组织架构图：按部门树统计人数，并输出 JSON、Graphviz DOT 与 Mermaid 文本
+use anyhow::{bail, Result};
+use serde::Serialize;
+use crate::common::tree::{self, TreeItem, TreeNode};
+
+/// 组织架构图输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartFormat {
+    Json,
+    Dot,
+    Mermaid,
+}
+
+impl ChartFormat {
+    /// 按名称解析，忽略大小写
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "mermaid" | "mmd" => Ok(Self::Mermaid),
+            other => bail!("不支持的组织架构图格式：{}", other),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Dot => "dot",
+            Self::Mermaid => "mmd",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json; charset=utf-8",
+            Self::Dot => "text/vnd.graphviz; charset=utf-8",
+            Self::Mermaid => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+/// 组织架构图中的部门
+#[derive(Debug, Clone, Serialize)]
+pub struct OrgUnit {
+    pub department_id: i64,
+    pub department_name: String,
+    pub parent_id: i64,
+    #[serde(skip)]
+    pub sort: i64,
+    pub manager_id: i64,
+    pub manager_name: String,
+    /// 本部门人数
+    pub headcount: usize,
+    /// 含所有下级部门的人数，不受层数限制影响
+    pub total_headcount: usize,
+    /// 本部门员工姓名，未要求时为空
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub employees: Vec<String>,
+}
+
+impl TreeItem for OrgUnit {
+    fn id(&self) -> i64 {
+        self.department_id
+    }
+
+    fn parent_id(&self) -> i64 {
+        self.parent_id
+    }
+
+    fn sort_key(&self) -> i64 {
+        self.sort
+    }
+}
+
+/// 组装架构图：root 为根部门（默认全部顶级部门），depth 为保留的层数（1 表示只保留根）
+pub fn build(units: Vec<OrgUnit>, root: Option<i64>, depth: Option<usize>) -> Result<Vec<TreeNode<OrgUnit>>> {
+    let forest = tree::build(units, tree::Orphans::Root);
+    let mut roots = match root {
+        Some(id) => match tree::find(&forest.roots, id) {
+            Some(node) => vec![node.clone()],
+            None => bail!("部门不存在：{}", id),
+        },
+        None => forest.roots,
+    };
+    for node in roots.iter_mut() {
+        total(node);
+    }
+    if let Some(depth) = depth {
+        truncate(&mut roots, depth.max(1));
+    }
+    Ok(roots)
+}
+
+fn total(node: &mut TreeNode<OrgUnit>) -> usize {
+    let children: usize = node.children.iter_mut().map(total).sum();
+    node.data.total_headcount = node.data.headcount + children;
+    node.data.total_headcount
+}
+
+fn truncate(nodes: &mut [TreeNode<OrgUnit>], depth: usize) {
+    for node in nodes {
+        if depth <= 1 {
+            node.children.clear();
+        } else {
+            truncate(&mut node.children, depth - 1);
+        }
+    }
+}
+
+/// 输出为 Graphviz DOT
+pub fn to_dot(roots: &[TreeNode<OrgUnit>]) -> String {
+    let mut out = String::from("digraph org {\n    rankdir=TB;\n    node [shape=box, style=rounded, fontname=\"sans-serif\"];\n");
+    for (_, unit) in tree::flatten(roots) {
+        let label = label_lines(unit)
+            .iter()
+            .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+            .collect::<Vec<_>>()
+            .join("\\n");
+        out.push_str(&format!("    d{} [label=\"{}\"];\n", unit.department_id, label));
+    }
+    for_each_edge(roots, &mut |parent, child| {
+        out.push_str(&format!("    d{} -> d{};\n", parent, child));
+    });
+    out.push_str("}\n");
+    out
+}
+
+/// 输出为 Mermaid 流程图
+pub fn to_mermaid(roots: &[TreeNode<OrgUnit>]) -> String {
+    let mut out = String::from("flowchart TD\n");
+    for (_, unit) in tree::flatten(roots) {
+        let label = label_lines(unit)
+            .iter()
+            .map(|line| escape_mermaid(line))
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        out.push_str(&format!("    d{}[\"{}\"]\n", unit.department_id, label));
+    }
+    for_each_edge(roots, &mut |parent, child| {
+        out.push_str(&format!("    d{} --> d{}\n", parent, child));
+    });
+    out
+}
+
+// Mermaid 节点文字按 HTML 渲染，特殊字符改用实体编码，换行会破坏节点语法，替换为空格
+fn escape_mermaid(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => escaped.push_str("#35;"),
+            '"' => escaped.push_str("#quot;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '&' => escaped.push_str("#amp;"),
+            '\r' | '\n' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 节点文字：部门名称、负责人、人数（本部门/合计）、员工姓名
+fn label_lines(unit: &OrgUnit) -> Vec<String> {
+    let mut lines = vec![unit.department_name.clone()];
+    if !unit.manager_name.is_empty() {
+        lines.push(format!("负责人：{}", unit.manager_name));
+    }
+    lines.push(format!("人数：{}/{}", unit.headcount, unit.total_headcount));
+    if !unit.employees.is_empty() {
+        lines.push(unit.employees.join("、"));
+    }
+    lines
+}
+
+fn for_each_edge<F: FnMut(i64, i64)>(nodes: &[TreeNode<OrgUnit>], f: &mut F) {
+    for node in nodes {
+        for child in &node.children {
+            f(node.data.department_id, child.data.department_id);
+        }
+        for_each_edge(&node.children, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(id: i64, name: &str, parent_id: i64, headcount: usize) -> OrgUnit {
+        OrgUnit {
+            department_id: id,
+            department_name: name.to_string(),
+            parent_id,
+            sort: 0,
+            manager_id: 0,
+            manager_name: String::new(),
+            headcount,
+            total_headcount: 0,
+            employees: Vec::new(),
+        }
+    }
+
+    fn units() -> Vec<OrgUnit> {
+        vec![unit(1, "总部", 0, 2), unit(2, "研发部", 1, 5), unit(3, "前端组", 2, 3)]
+    }
+
+    #[test]
+    fn test_build() {
+        let roots = build(units(), None, None).unwrap();
+        assert_eq!(roots[0].data.total_headcount, 10);
+        assert_eq!(roots[0].children[0].data.total_headcount, 8);
+
+        // 限制层数后合计人数仍包含隐藏的下级部门
+        let roots = build(units(), Some(2), Some(1)).unwrap();
+        assert_eq!(roots.len(), 1);
+        assert!(roots[0].children.is_empty());
+        assert_eq!(roots[0].data.total_headcount, 8);
+
+        assert!(build(units(), Some(9), None).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut units = units();
+        units[1].manager_name = "张\"三".to_string();
+        let roots = build(units, None, None).unwrap();
+
+        let dot = to_dot(&roots);
+        assert!(dot.contains("d2 [label=\"研发部\\n负责人：张\\\"三\\n人数：5/8\"];"));
+        assert!(dot.contains("d1 -> d2;"));
+
+        let mermaid = to_mermaid(&roots);
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("d2 --> d3"));
+        assert!(mermaid.contains("负责人：张#quot;三"));
+    }
+
+    #[test]
+    fn test_render_hostile_name() {
+        let mut units = units();
+        units[2].department_name = "<img src=x onerror=alert(1)>&#quot;\n]\n    d1 --> d3".to_string();
+        units[2].employees = vec!["<b>李四</b>".to_string()];
+        let roots = build(units, None, None).unwrap();
+
+        let mermaid = to_mermaid(&roots);
+        let node = mermaid.lines().find(|line| line.trim_start().starts_with("d3[")).unwrap();
+        assert_eq!(
+            node,
+            "    d3[\"#lt;img src=x onerror=alert(1)#gt;#amp;#35;quot; ]     d1 --#gt; d3<br/>人数：3/3<br/>#lt;b#gt;李四#lt;/b#gt;\"]"
+        );
+        // 节点与连线数量不受名称影响
+        assert_eq!(mermaid.lines().count(), 1 + 3 + 2);
+        assert!(!mermaid.replace("<br/>", "").contains('<'));
+    }
+
+    #[test]
+    fn test_format_parse() {
+        assert_eq!(ChartFormat::parse("DOT").unwrap(), ChartFormat::Dot);
+        assert_eq!(ChartFormat::parse("mermaid").unwrap().extension(), "mmd");
+        assert!(ChartFormat::parse("svg").is_err());
+    }
+}
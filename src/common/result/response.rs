@@ -1,8 +1,14 @@
 use axum::{
+    body::Body,
+    http::header,
     response::{IntoResponse, Response},
     Json,
 };
+use bytes::Bytes;
 use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use std::fmt;
 use super::status::Status;
 
 pub struct ApiOK<T>(pub Option<T>)
@@ -20,6 +26,49 @@ where
     }
 }
 
+/// 文件下载响应
+pub struct ApiFile {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+impl IntoResponse for ApiFile {
+    fn into_response(self) -> Response {
+        let disposition = format!("attachment; filename=\"{}\"", self.filename);
+        (
+            [
+                (header::CONTENT_TYPE, self.content_type.to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            self.data,
+        )
+            .into_response()
+    }
+}
+
+/// 流式文件下载响应，数据由后台任务分批写入通道
+pub struct ApiFileStream {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub rx: mpsc::Receiver<std::io::Result<Bytes>>,
+}
+
+impl IntoResponse for ApiFileStream {
+    fn into_response(self) -> Response {
+        let disposition = format!("attachment; filename=\"{}\"", self.filename);
+        (
+            [
+                (header::CONTENT_TYPE, self.content_type.to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            Body::from_stream(ReceiverStream::new(self.rx)),
+        )
+            .into_response()
+    }
+}
+
+#[derive(Debug)]
 pub enum ApiErr {
     Error(i32, String),
     ErrParams(Option<String>),
@@ -33,21 +82,37 @@ pub enum ApiErr {
 
 use ApiErr::*;
 
-impl IntoResponse for ApiErr {
-    fn into_response(self) -> Response {
-        let status: Status<()> = match self {
-            Error(code, msg) => Status::Err(code, msg),
-            ErrParams(msg) => Status::Err(10000, msg.unwrap_or(String::from("参数错误"))),
-            ErrAuth(msg) => Status::Err(20000, msg.unwrap_or(String::from("未授权，请先登录"))),
-            ErrPerm(msg) => Status::Err(30000, msg.unwrap_or(String::from("权限不足"))),
-            ErrNotFound(msg) => Status::Err(40000, msg.unwrap_or(String::from("数据不存在"))),
-            ErrSystem(msg) => Status::Err(
+impl ApiErr {
+    fn code_msg(&self) -> (i32, String) {
+        match self {
+            Error(code, msg) => (*code, msg.clone()),
+            ErrParams(msg) => (10000, msg.clone().unwrap_or(String::from("参数错误"))),
+            ErrAuth(msg) => (20000, msg.clone().unwrap_or(String::from("未授权，请先登录"))),
+            ErrPerm(msg) => (30000, msg.clone().unwrap_or(String::from("权限不足"))),
+            ErrNotFound(msg) => (40000, msg.clone().unwrap_or(String::from("数据不存在"))),
+            ErrSystem(msg) => (
                 50000,
-                msg.unwrap_or(String::from("内部服务器错误，请稍后重试")),
+                msg.clone().unwrap_or(String::from("内部服务器错误，请稍后重试")),
             ),
-            ErrData(msg) => Status::Err(60000, msg.unwrap_or(String::from("数据异常"))),
-            ErrService(msg) => Status::Err(70000, msg.unwrap_or(String::from("服务异常"))),
-        };
+            ErrData(msg) => (60000, msg.clone().unwrap_or(String::from("数据异常"))),
+            ErrService(msg) => (70000, msg.clone().unwrap_or(String::from("服务异常"))),
+        }
+    }
+}
+
+impl fmt::Display for ApiErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (code, msg) = self.code_msg();
+        write!(f, "[{}] {}", code, msg)
+    }
+}
+
+impl std::error::Error for ApiErr {}
+
+impl IntoResponse for ApiErr {
+    fn into_response(self) -> Response {
+        let (code, msg) = self.code_msg();
+        let status: Status<()> = Status::Err(code, msg);
         Json(status.to_reply()).into_response()
     }
 }
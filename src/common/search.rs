@@ -0,0 +1,214 @@
+//! 模糊搜索：原文、拼音全拼与首字母匹配，打分并高亮
+use std::collections::HashMap;
+use pinyin::ToPinyin;
+use sea_orm::sea_query::LikeExpr;
+use serde::Serialize;
+
+/// 匹配方式，越靠后优先级越高
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    InitialsContains,
+    InitialsPrefix,
+    PinyinContains,
+    PinyinPrefix,
+    Contains,
+    Prefix,
+    Exact,
+}
+
+/// 匹配结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub kind: MatchKind,
+    /// 分值，越大越靠前；同一匹配方式下文本越短分值越高
+    pub score: u32,
+    /// 命中部分以 `<em>` 包裹，其余内容已做 HTML 转义
+    pub highlight: String,
+}
+
+/// 文本的拼音检索键：(全拼, 首字母)，均为小写，非汉字原样保留
+pub fn keys(text: &str) -> (String, String) {
+    let pinyin = segments(text, Mode::Pinyin).concat();
+    let initials = segments(text, Mode::Initials).concat();
+    (pinyin, initials)
+}
+
+/// 数据库初筛的候选数量上限，打分排序后再截取
+pub const CANDIDATES: u64 = 200;
+
+/// 查询参数中的返回条数，默认 20，最多 100
+pub fn limit(query: &HashMap<String, String>) -> usize {
+    query
+        .get("limit")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(20)
+        .clamp(1, 100)
+}
+
+/// 转义 LIKE 模式中的 `%`、`_` 与 `\`，使关键字按字面匹配
+pub fn escape_like(keyword: &str) -> String {
+    let mut escaped = String::with_capacity(keyword.len());
+    for c in keyword.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 包含关键字的 LIKE 模式
+pub fn like_contains(keyword: &str) -> LikeExpr {
+    LikeExpr::new(format!("%{}%", escape_like(keyword))).escape('\\')
+}
+
+/// 以关键字开头的 LIKE 模式
+pub fn like_prefix(keyword: &str) -> LikeExpr {
+    LikeExpr::new(format!("{}%", escape_like(keyword))).escape('\\')
+}
+
+/// 规范化搜索关键字：去除首尾空白并转为小写
+pub fn normalize(keyword: &str) -> String {
+    keyword.trim().to_lowercase()
+}
+
+/// 拼音匹配使用的关键字，去除其中的空白，如 "zhang san"
+pub fn pinyin_keyword(keyword: &str) -> String {
+    keyword.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// 在文本中匹配已规范化的关键字，依次尝试原文、全拼、首字母
+///
+/// `pinyin` 为 false 时只匹配原文，用于登录名、手机号等字段
+pub fn matches(text: &str, keyword: &str, pinyin: bool) -> Option<Match> {
+    if keyword.is_empty() || text.is_empty() {
+        return None;
+    }
+
+    let modes: &[Mode] = if pinyin { &[Mode::Text, Mode::Pinyin, Mode::Initials] } else { &[Mode::Text] };
+    for &mode in modes {
+        let keyword = if mode == Mode::Text { keyword.to_string() } else { pinyin_keyword(keyword) };
+        let segments = segments(text, mode);
+        if let Some((start, end)) = find(&segments, &keyword) {
+            let joined_len: usize = segments.iter().map(String::len).sum();
+            let prefix = start == 0;
+            let kind = match mode {
+                Mode::Text if prefix && end == segments.len() && keyword.len() == joined_len => MatchKind::Exact,
+                Mode::Text if prefix => MatchKind::Prefix,
+                Mode::Text => MatchKind::Contains,
+                Mode::Pinyin if prefix => MatchKind::PinyinPrefix,
+                Mode::Pinyin => MatchKind::PinyinContains,
+                Mode::Initials if prefix => MatchKind::InitialsPrefix,
+                Mode::Initials => MatchKind::InitialsContains,
+            };
+            let length = text.chars().count().min(99) as u32;
+            return Some(Match {
+                kind,
+                score: (kind as u32 + 1) * 100 + (99 - length),
+                highlight: highlight(text, start, end),
+            });
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Text,
+    Pinyin,
+    Initials,
+}
+
+// 每个字符对应的匹配片段
+fn segments(text: &str, mode: Mode) -> Vec<String> {
+    text.chars()
+        .map(|c| match (mode, c.to_pinyin()) {
+            (Mode::Pinyin, Some(pinyin)) => pinyin.plain().to_string(),
+            (Mode::Initials, Some(pinyin)) => pinyin.first_letter().to_string(),
+            _ => c.to_lowercase().collect(),
+        })
+        .collect()
+}
+
+// 查找从某个字符边界开始的匹配，返回覆盖的字符区间 [start, end)
+fn find(segments: &[String], keyword: &str) -> Option<(usize, usize)> {
+    let joined = segments.concat();
+    for start in 0..segments.len() {
+        let offset: usize = segments[..start].iter().map(String::len).sum();
+        if !joined[offset..].starts_with(keyword) {
+            continue;
+        }
+        let mut end = start;
+        let mut covered = offset;
+        while covered < offset + keyword.len() {
+            covered += segments[end].len();
+            end += 1;
+        }
+        return Some((start, end));
+    }
+    None
+}
+
+fn highlight(text: &str, start: usize, end: usize) -> String {
+    let mut result = String::with_capacity(text.len() + 9);
+    for (index, c) in text.chars().enumerate() {
+        if index == start {
+            result.push_str("<em>");
+        }
+        match c {
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '&' => result.push_str("&amp;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+        if index + 1 == end {
+            result.push_str("</em>");
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("张三"), "张三");
+        assert_eq!(escape_like(r"50%_a\b"), r"50\%\_a\\b");
+    }
+
+    #[test]
+    fn test_keys() {
+        assert_eq!(keys("张三"), ("zhangsan".to_string(), "zs".to_string()));
+        assert_eq!(keys("Tom李"), ("tomli".to_string(), "toml".to_string()));
+    }
+
+    #[test]
+    fn test_matches() {
+        let exact = matches("张三", "张三", true).unwrap();
+        assert_eq!(exact.kind, MatchKind::Exact);
+        assert_eq!(exact.highlight, "<em>张三</em>");
+
+        let pinyin = matches("张三丰", "zhangsan", true).unwrap();
+        assert_eq!(pinyin.kind, MatchKind::PinyinPrefix);
+        assert_eq!(pinyin.highlight, "<em>张三</em>丰");
+
+        let initials = matches("李张三", "zs", true).unwrap();
+        assert_eq!(initials.kind, MatchKind::InitialsContains);
+        assert_eq!(initials.highlight, "李<em>张三</em>");
+
+        // 只能从音节开头匹配
+        assert!(matches("张三", "angsan", true).is_none());
+        assert!(matches("张三", "zs", false).is_none());
+        assert!(exact.score > pinyin.score && pinyin.score > initials.score);
+    }
+
+    #[test]
+    fn test_highlight_escape() {
+        let m = matches("<a>bc", "b", false).unwrap();
+        assert_eq!(m.highlight, "&lt;a&gt;<em>b</em>c");
+    }
+}
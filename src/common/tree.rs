@@ -1,80 +1,294 @@
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+/// 可组装成树的数据，`parent_id` 为 0 表示根节点
+pub trait TreeItem {
+    fn id(&self) -> i64;
+    fn parent_id(&self) -> i64;
+
+    /// 同级节点的排序值，相同时按 id 排序
+    fn sort_key(&self) -> i64 {
+        0
+    }
+}
+
+/// 树节点，序列化时节点数据与 children 平铺在同一层
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TreeNode {
+pub struct TreeNode<T> {
+    #[serde(flatten)]
+    pub data: T,
+    pub children: Vec<TreeNode<T>>,
+}
+
+/// 仅包含 id 与名称的节点数据，用于下拉树
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Item {
     pub id: i64,
     pub name: String,
-    pub children: Vec<TreeNode>,
+    #[serde(skip)]
+    pub parent_id: i64,
+    #[serde(skip)]
+    pub sort: i64,
 }
 
-pub fn build_tree(nodes: Vec<(i64, String, Option<i64>)>) -> Option<Vec<TreeNode>> {
-    if nodes.is_empty() {
-        return None;
+impl TreeItem for Item {
+    fn id(&self) -> i64 {
+        self.id
     }
 
-    let mut children_by_parent: HashMap<i64, Vec<TreeNode>> = HashMap::new();
-    let mut root_nodes = Vec::new();
+    fn parent_id(&self) -> i64 {
+        self.parent_id
+    }
 
-    // 第一步：创建所有节点，并识别根节点
-    for (id, name, parent_id) in nodes {
-        let node = TreeNode {
-            id,
-            name,
-            children: Vec::new(),
-        };
-        
-        match parent_id {
-            Some(parent_id) => {
-                children_by_parent.entry(parent_id)
-                    .or_insert_with(Vec::new)
-                    .push(node);
-            }
-            None => root_nodes.push(node),
+    fn sort_key(&self) -> i64 {
+        self.sort
+    }
+}
+
+/// 孤儿节点（父节点不存在）的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orphans {
+    /// 作为根节点保留
+    Root,
+    /// 连同其子树一起丢弃
+    Drop,
+}
+
+/// 组装结果
+#[derive(Debug, Clone)]
+pub struct Forest<T> {
+    pub roots: Vec<TreeNode<T>>,
+    /// 父节点不存在的节点ID
+    pub orphans: Vec<i64>,
+    /// 处于循环引用中、无法挂到任何根节点下的节点ID
+    pub cycles: Vec<i64>,
+}
+
+/// 按 parent_id 组装树，同级节点按 sort_key、id 排序
+///
+/// 每个节点只会被取出一次，循环引用不会导致死循环，而是在 `cycles` 中返回
+pub fn build<T: TreeItem>(items: Vec<T>, orphans: Orphans) -> Forest<T> {
+    let ids: HashSet<i64> = items.iter().map(|item| item.id()).collect();
+
+    let mut children_by_parent: HashMap<i64, Vec<usize>> = HashMap::new();
+    let mut root_indexes = Vec::new();
+    let mut orphan_indexes = Vec::new();
+
+    // 第一步：按父节点分组，并识别根节点与孤儿节点
+    for (index, item) in items.iter().enumerate() {
+        let parent_id = item.parent_id();
+        if parent_id == 0 {
+            root_indexes.push(index);
+        } else if ids.contains(&parent_id) {
+            children_by_parent.entry(parent_id).or_default().push(index);
+        } else {
+            orphan_indexes.push(index);
         }
     }
 
-    // 如果没有根节点，返回 None
-    if root_nodes.is_empty() && !children_by_parent.is_empty() {
-        return None; // 可能存在循环引用
+    let orphan_ids = orphan_indexes.iter().map(|&index| items[index].id()).collect();
+    if orphans == Orphans::Root {
+        root_indexes.append(&mut orphan_indexes);
+    }
+
+    let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+
+    // 第二步：从根节点开始逐层挂载子节点
+    sort_indexes(&slots, &mut root_indexes);
+    let roots = root_indexes
+        .into_iter()
+        .filter_map(|index| attach(index, &mut slots, &mut children_by_parent))
+        .collect();
+
+    // 丢弃的孤儿子树同样取出，剩余未取出的节点即处于循环引用中
+    for index in orphan_indexes {
+        attach(index, &mut slots, &mut children_by_parent);
+    }
+    let cycles = slots.iter().flatten().map(|item| item.id()).collect();
+
+    Forest {
+        roots,
+        orphans: orphan_ids,
+        cycles,
     }
+}
+
+fn attach<T: TreeItem>(
+    index: usize,
+    slots: &mut [Option<T>],
+    children_by_parent: &mut HashMap<i64, Vec<usize>>,
+) -> Option<TreeNode<T>> {
+    let data = slots[index].take()?;
+    let mut child_indexes = children_by_parent.remove(&data.id()).unwrap_or_default();
+    sort_indexes(slots, &mut child_indexes);
+
+    let children = child_indexes
+        .into_iter()
+        .filter_map(|child| attach(child, slots, children_by_parent))
+        .collect();
+    Some(TreeNode { data, children })
+}
 
-    // 递归构建树，添加循环检测
-    fn build_subtree(
-        node: &mut TreeNode, 
-        children_by_parent: &HashMap<i64, Vec<TreeNode>>,
-        visited: &mut Vec<i64>
-    ) -> bool {
-        if visited.contains(&node.id) {
-            return false; // 检测到循环引用
+fn sort_indexes<T: TreeItem>(slots: &[Option<T>], indexes: &mut [usize]) {
+    indexes.sort_by_key(|&index| {
+        slots[index]
+            .as_ref()
+            .map(|item| (item.sort_key(), item.id()))
+            .unwrap_or_default()
+    });
+}
+
+/// 在树中查找节点
+pub fn find<T: TreeItem>(roots: &[TreeNode<T>], id: i64) -> Option<&TreeNode<T>> {
+    let mut stack: Vec<&TreeNode<T>> = roots.iter().collect();
+    while let Some(node) = stack.pop() {
+        if node.data.id() == id {
+            return Some(node);
         }
-        
-        visited.push(node.id);
-        
-        if let Some(children) = children_by_parent.get(&node.id) {
-            node.children = children.clone();
-            for child in &mut node.children {
-                if !build_subtree(child, children_by_parent, visited) {
-                    return false;
-                }
-            }
+        stack.extend(node.children.iter());
+    }
+    None
+}
+
+/// 节点自身及其所有子孙节点的ID，节点不存在时返回空
+pub fn subtree_ids<T: TreeItem>(roots: &[TreeNode<T>], id: i64) -> Vec<i64> {
+    find(roots, id)
+        .map(|node| flatten(std::slice::from_ref(node)).into_iter().map(|(_, data)| data.id()).collect())
+        .unwrap_or_default()
+}
+
+/// 从根节点到该节点的ID路径（含自身），遇到循环引用时截断
+pub fn ancestors<T: TreeItem>(items: &[T], id: i64) -> Vec<i64> {
+    let parents: HashMap<i64, i64> = items.iter().map(|item| (item.id(), item.parent_id())).collect();
+    let mut path = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = id;
+    while parents.contains_key(&current) && visited.insert(current) {
+        path.push(current);
+        current = parents[&current];
+    }
+    path.reverse();
+    path
+}
+
+/// 先序遍历展开为 (深度, 节点数据) 列表，根节点深度为 0
+pub fn flatten<T>(roots: &[TreeNode<T>]) -> Vec<(usize, &T)> {
+    fn walk<'a, T>(nodes: &'a [TreeNode<T>], depth: usize, out: &mut Vec<(usize, &'a T)>) {
+        for node in nodes {
+            out.push((depth, &node.data));
+            walk(&node.children, depth + 1, out);
         }
-        
-        visited.pop();
-        true
     }
 
-    // 为每个根节点构建子树
-    let mut visited = Vec::new();
-    for root in &mut root_nodes {
-        if !build_subtree(root, &children_by_parent, &mut visited) {
-            return None; // 如果检测到循环引用，返回 None
+    let mut out = Vec::new();
+    walk(roots, 0, &mut out);
+    out
+}
+
+/// 按条件裁剪：保留满足条件的节点及其上级路径
+pub fn prune<T, F>(roots: Vec<TreeNode<T>>, keep: &F) -> Vec<TreeNode<T>>
+where
+    F: Fn(&T) -> bool,
+{
+    roots
+        .into_iter()
+        .filter_map(|mut node| {
+            node.children = prune(node.children, keep);
+            if keep(&node.data) || !node.children.is_empty() {
+                Some(node)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i64, parent_id: i64, sort: i64) -> Item {
+        Item {
+            id,
+            name: format!("n{}", id),
+            parent_id,
+            sort,
         }
     }
 
-    if root_nodes.is_empty() {
-        None
-    } else {
-        Some(root_nodes)
+    fn ids<T: TreeItem>(nodes: &[TreeNode<T>]) -> Vec<i64> {
+        nodes.iter().map(|node| node.data.id()).collect()
+    }
+
+    #[test]
+    fn test_build_sorted() {
+        let forest = build(
+            vec![item(1, 0, 2), item(2, 0, 1), item(3, 1, 5), item(4, 1, 0), item(5, 4, 0)],
+            Orphans::Root,
+        );
+        assert_eq!(ids(&forest.roots), vec![2, 1]);
+        assert_eq!(ids(&forest.roots[1].children), vec![4, 3]);
+        assert_eq!(ids(&forest.roots[1].children[0].children), vec![5]);
+        assert!(forest.orphans.is_empty());
+        assert!(forest.cycles.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_orphans() {
+        let items = vec![item(1, 0, 0), item(2, 99, 0), item(3, 2, 0)];
+
+        let forest = build(items.clone(), Orphans::Root);
+        assert_eq!(ids(&forest.roots), vec![1, 2]);
+        assert_eq!(forest.orphans, vec![2]);
+        assert!(forest.cycles.is_empty());
+
+        let forest = build(items, Orphans::Drop);
+        assert_eq!(ids(&forest.roots), vec![1]);
+        assert_eq!(forest.orphans, vec![2]);
+        assert!(forest.cycles.is_empty());
+    }
+
+    #[test]
+    fn test_build_cycles() {
+        let forest = build(
+            vec![item(1, 0, 0), item(2, 3, 0), item(3, 2, 0), item(4, 4, 0)],
+            Orphans::Root,
+        );
+        assert_eq!(ids(&forest.roots), vec![1]);
+        let mut cycles = forest.cycles;
+        cycles.sort();
+        assert_eq!(cycles, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_helpers() {
+        let items = vec![item(1, 0, 0), item(2, 1, 0), item(3, 2, 0), item(4, 1, 1), item(5, 0, 1)];
+        let roots = build(items.clone(), Orphans::Root).roots;
+
+        assert_eq!(find(&roots, 2).map(|node| ids(&node.children)), Some(vec![3]));
+        assert!(find(&roots, 99).is_none());
+
+        let mut subtree = subtree_ids(&roots, 2);
+        subtree.sort();
+        assert_eq!(subtree, vec![2, 3]);
+        assert!(subtree_ids(&roots, 99).is_empty());
+
+        assert_eq!(ancestors(&items, 3), vec![1, 2, 3]);
+        assert!(ancestors(&items, 99).is_empty());
+
+        let flat: Vec<(usize, i64)> = flatten(&roots).into_iter().map(|(d, i)| (d, i.id)).collect();
+        assert_eq!(flat, vec![(0, 1), (1, 2), (2, 3), (1, 4), (0, 5)]);
+
+        let pruned = prune(roots, &|i: &Item| i.id == 3);
+        assert_eq!(ids(&pruned), vec![1]);
+        assert_eq!(ids(&pruned[0].children), vec![2]);
+        assert_eq!(ids(&pruned[0].children[0].children), vec![3]);
+    }
+
+    #[test]
+    fn test_serialize_flatten() {
+        let roots = build(vec![item(1, 0, 0), item(2, 1, 0)], Orphans::Root).roots;
+        let json = serde_json::to_string(&roots).unwrap();
+        assert_eq!(json, r#"[{"id":1,"name":"n1","children":[{"id":2,"name":"n2","children":[]}]}]"#);
+    }
+}
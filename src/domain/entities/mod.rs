@@ -4,6 +4,8 @@ pub mod prelude;
 
 pub mod t_department;
 pub mod t_employee;
+pub mod t_employee_history;
+pub mod t_employee_offboard;
 pub mod t_menu;
 pub mod t_operate_log;
 pub mod t_position;
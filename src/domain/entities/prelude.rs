@@ -2,6 +2,8 @@
 
 pub use super::t_department::Entity as TDepartment;
 pub use super::t_employee::Entity as TEmployee;
+pub use super::t_employee_history::Entity as TEmployeeHistory;
+pub use super::t_employee_offboard::Entity as TEmployeeOffboard;
 pub use super::t_menu::Entity as TMenu;
 pub use super::t_operate_log::Entity as TOperateLog;
 pub use super::t_position::Entity as TPosition;
@@ -8,8 +8,12 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub department_id: i64,
     pub department_name: String,
+    pub name_pinyin: String,
+    pub name_initials: String,
     pub manager_id: i64,
     pub parent_id: i64,
+    pub path: String,
+    pub depth: i32,
     pub sort: i32,
     pub update_time: i64,
     pub create_time: i64,
@@ -10,6 +10,8 @@ pub struct Model {
     pub login_name: String,
     pub login_pwd: String,
     pub realname: String,
+    pub name_pinyin: String,
+    pub name_initials: String,
     pub avatar: String,
     pub gender: u8,
     pub phone: String,
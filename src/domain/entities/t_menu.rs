@@ -26,7 +26,7 @@ pub struct Model {
     pub cache_flag: i8,
     pub visible_flag: i8,
     pub disabled_flag: i8,
-    pub deleted_flag: i8,
+    pub deleted_flag: u8,
     pub create_user_id: i64,
     pub create_time: i64,
     pub update_user_id: Option<i64>,
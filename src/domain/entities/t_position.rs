@@ -11,7 +11,7 @@ pub struct Model {
     pub level: String,
     pub sort: i64,
     pub remark: String,
-    pub deleted_flag: i64,
+    pub deleted_flag: u8,
     pub create_time: i64,
     pub update_time: i64,
 }
@@ -9,6 +9,7 @@ pub struct Model {
     pub role_id: i64,
     pub role_name: String,
     pub role_code: String,
+    pub parent_id: i64,
     pub remark:String,
     pub update_time: i64,
     pub create_time: i64,
@@ -9,6 +9,8 @@ pub struct Model {
     pub id: i64,
     pub role_id: i64,
     pub employee_id: i64,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
     pub update_time: i64,
     pub create_time: i64,
 }
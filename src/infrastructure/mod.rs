@@ -1,3 +1,4 @@
 pub mod security;
 pub mod persistence;
-pub mod repository;
\ No newline at end of file
+pub mod repository;
+pub mod storage;
\ No newline at end of file
@@ -1,23 +1,112 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use crate::infrastructure::persistence::database as db;
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set
+    ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    Condition, ConnectionTrait, TransactionTrait,
 };
-use crate::application::dto::department_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelect};
+use crate::application::dto::department_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSearch,
+    ReqMove, ReqReorder, RespPathNode, RespReport,
+    ReqMerge, RespMerge, RespMergeEmployee};
 use crate::domain::entities::{
     t_department, prelude::TDepartment,
-    t_employee, prelude::TEmployee
+    t_employee, prelude::TEmployee,
+    t_operate_log, prelude::TOperateLog,
 };
+use super::employee_history_repository::{self as history, Change};
 
 use crate::common::{
     result::response::{ApiErr, ApiOK, Result},
     utils,xtime,
-    tree,
+    tree, search,
+    orgchart::OrgUnit,
 };
 
 use time::macros::offset;
+use sea_orm::{prelude::Expr, sea_query::SimpleExpr, Value};
 
 
+/// 部门自身及其所有下级部门的ID，部门不存在时返回空，可在事务中读取修改后的层级
+pub async fn subtree_in<C: ConnectionTrait>(db: &C, department_id: i64) -> Result<Vec<i64>> {
+    let Some(model) = TDepartment::find_by_id(department_id).one(db).await.map_err(|e| {
+        tracing::error!(error = ?e, "error find t_department");
+        ApiErr::ErrSystem(None)
+    })?
+    else {
+        return Ok(Vec::new());
+    };
+    TDepartment::find()
+        .select_only()
+        .column(t_department::Column::DepartmentId)
+        .filter(t_department::Column::Path.starts_with(DepartmentRepository::indexed_path(&model)?))
+        .into_tuple::<i64>()
+        .all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_department");
+            ApiErr::ErrSystem(None)
+        })
+}
+
+/// 校验部门负责人仍满足规则：员工存在且启用，并属于本部门或上级部门链中的部门
+///
+/// 在修改部门层级、员工所属部门或负责人的事务中、写入之后调用，返回错误时由调用方回滚
+pub async fn check_managers<C: ConnectionTrait>(db: &C, department_ids: Vec<i64>) -> Result<()> {
+    let departments = TDepartment::find()
+        .filter(t_department::Column::DepartmentId.is_in(department_ids))
+        .filter(t_department::Column::ManagerId.ne(0))
+        .order_by(t_department::Column::Path, Order::Asc)
+        .all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_department");
+            ApiErr::ErrSystem(None)
+        })?;
+    if departments.is_empty() {
+        return Ok(());
+    }
+    let managers: HashMap<i64, t_employee::Model> = TEmployee::find()
+        .filter(t_employee::Column::EmployeeId.is_in(departments.iter().map(|d| d.manager_id)))
+        .filter(t_employee::Column::DeletedFlag.eq(0))
+        .all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_employee");
+            ApiErr::ErrSystem(None)
+        })?
+        .into_iter()
+        .map(|employee| (employee.employee_id, employee))
+        .collect();
+
+    for department in departments {
+        let message = match managers.get(&department.manager_id) {
+            None => "负责人不存在",
+            Some(employee) if employee.disabled_flag == 1 => "负责人账号已禁用",
+            Some(employee) if !DepartmentRepository::path_ids(&department.path).contains(&employee.department_id) => {
+                "负责人必须是本部门或上级部门的员工"
+            }
+            Some(_) => continue,
+        };
+        return Err(ApiErr::ErrParams(Some(format!("部门[{}]的{}", department.department_name, message))));
+    }
+    Ok(())
+}
+
+/// 校验这些员工负责的部门，员工调整部门、被禁用、删除或离职后调用
+pub async fn check_managed_by<C: ConnectionTrait>(db: &C, employee_ids: &[i64]) -> Result<()> {
+    let department_ids = TDepartment::find()
+        .select_only()
+        .column(t_department::Column::DepartmentId)
+        .filter(t_department::Column::ManagerId.is_in(employee_ids.iter().copied()))
+        .into_tuple::<i64>()
+        .all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_department");
+            ApiErr::ErrSystem(None)
+        })?;
+    check_managers(db, department_ids).await
+}
+
 pub struct DepartmentRepository{
     conn: DatabaseConnection
 }
@@ -43,52 +132,511 @@ impl DepartmentRepository {
     if count > 0 {
         return Err(ApiErr::ErrPerm(Some("部门名称重复".to_string())));
     }
+    self.check_manager(0, req.parentid, req.managerid).await?;
 
     // 创建数据对象
     let now = xtime::now(offset!(+8)).unix_timestamp();
+    let (name_pinyin, name_initials) = search::keys(&req.deptname);
     let model = t_department::ActiveModel {
         department_name: Set(req.deptname),  
+        name_pinyin: Set(name_pinyin),
+        name_initials: Set(name_initials),
         sort: Set(req.sort),
         manager_id: Set(req.managerid),
         parent_id: Set(req.parentid),
         create_time: Set(now),
         ..Default::default()
     };
-    /* 插入数据 */
-    if let Err(e) = TDepartment::insert(model).exec(&self.conn).await {
+    /* 插入数据，ID 生成后补写层级路径；上级部门加锁读取，避免并发移动后写入过期路径 */
+    let txn = self.conn.begin().await.map_err(|e| {
+        tracing::error!(error = ?e, "error begin transaction");
+        ApiErr::ErrSystem(None)
+    })?;
+    Self::check_parent(&txn, 0, req.parentid).await?;
+    let parent_path = Self::parent_path(&txn, req.parentid).await?;
+    let result = TDepartment::insert(model).exec(&txn).await.map_err(|e| {
         tracing::error!(error = ?e, "error insert t_department");
-            return Err(ApiErr::ErrSystem(None));
-        }
+        ApiErr::ErrSystem(None)
+    })?;
+    let (path, depth) = Self::child_path(&parent_path, result.last_insert_id);
+    let model = t_department::ActiveModel {
+        department_id: Set(result.last_insert_id),
+        path: Set(path),
+        depth: Set(depth),
+        ..Default::default()
+    };
+    if let Err(e) = TDepartment::update(model).exec(&txn).await {
+        tracing::error!(error = ?e, "error update t_department");
+        return Err(ApiErr::ErrSystem(None));
+    }
+    txn.commit().await.map_err(|e| {
+        tracing::error!(error = ?e, "error commit transaction");
+        ApiErr::ErrSystem(None)
+    })?;
 
     Ok(ApiOK(None))
     }
 
     // 查询部门列表
-    pub async fn select_list(&self) ->  Result<ApiOK<Vec<tree::TreeNode>>> {
-        let department_list= TDepartment::find()
+    pub async fn select_list(&self) ->  Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
+        let items = self.items().await?;
+        let forest = tree::build(items, tree::Orphans::Root);
+        if !forest.cycles.is_empty() {
+            tracing::warn!(cycles = ?forest.cycles, "t_department parent_id cycle detected");
+        }
+        Ok(ApiOK(Some(forest.roots)))
+    }
+
+    // 部门自身及其所有下级部门的ID，部门不存在时返回空
+    pub async fn subtree_ids(&self, department_id: i64) -> Result<Vec<i64>> {
+        subtree_in(&self.conn, department_id).await
+    }
+
+    // 上级部门链，从顶级部门到自身
+    pub async fn ancestors(&self, department_id: i64) -> Result<ApiOK<Vec<RespPathNode>>> {
+        let model = self.find_department(department_id).await?;
+        let ids = Self::path_ids(&model.path);
+        let models = TDepartment::find()
+            .filter(t_department::Column::DepartmentId.is_in(ids))
+            .order_by(t_department::Column::Depth, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        Ok(ApiOK(Some(models.into_iter().map(Self::path_node).collect())))
+    }
+
+    // 所有下级部门（不含自身），depth 限制相对层数，如 1 表示直接下级
+    pub async fn descendants(&self, department_id: i64, depth: Option<i32>) -> Result<ApiOK<Vec<RespPathNode>>> {
+        let model = self.find_department(department_id).await?;
+        let mut builder = TDepartment::find()
+            .filter(t_department::Column::Path.starts_with(Self::indexed_path(&model)?))
+            .filter(t_department::Column::DepartmentId.ne(department_id));
+        if let Some(depth) = depth {
+            builder = builder.filter(t_department::Column::Depth.lte(model.depth + depth));
+        }
+        let models = builder
+            .order_by(t_department::Column::Depth, Order::Asc)
+            .order_by(t_department::Column::Sort, Order::Asc)
+            .order_by(t_department::Column::DepartmentId, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        Ok(ApiOK(Some(models.into_iter().map(Self::path_node).collect())))
+    }
+
+    // 按 parent_id 重新生成全部部门的层级路径，返回更新的数量
+    // 处于循环引用中的部门无法确定路径，记录告警后跳过
+    pub async fn rebuild_path(&self) -> Result<u64> {
+        let models = TDepartment::find().all(&self.conn).await.map_err(|e| {
+            tracing::error!(error = ?e, "error find t_department");
+            ApiErr::ErrSystem(None)
+        })?;
+        let current: HashMap<i64, (String, i32)> =
+            models.iter().map(|m| (m.department_id, (m.path.clone(), m.depth))).collect();
+        let items = models
+            .into_iter()
+            .map(|m| tree::Item { id: m.department_id, name: String::new(), parent_id: m.parent_id, sort: m.sort as i64 })
+            .collect();
+        let forest = tree::build(items, tree::Orphans::Root);
+        if !forest.cycles.is_empty() {
+            tracing::warn!(cycles = ?forest.cycles, "t_department parent_id cycle detected");
+        }
+
+        // 先序遍历保证先算出上级路径
+        let mut paths: HashMap<i64, String> = HashMap::new();
+        let mut updated = 0;
+        for (_, item) in tree::flatten(&forest.roots) {
+            let parent_path = paths.get(&item.parent_id).cloned().unwrap_or_else(|| "/".to_string());
+            let (path, depth) = Self::child_path(&parent_path, item.id);
+            paths.insert(item.id, path.clone());
+            if current.get(&item.id) == Some(&(path.clone(), depth)) {
+                continue;
+            }
+            let model = t_department::ActiveModel {
+                department_id: Set(item.id),
+                path: Set(path),
+                depth: Set(depth),
+                ..Default::default()
+            };
+            if let Err(e) = TDepartment::update(model).exec(&self.conn).await {
+                tracing::error!(error = ?e, "error update t_department");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    // 上级变化后，按前缀替换整个子树的路径与深度
+    async fn move_path<C: ConnectionTrait>(&self, db: &C, model: &t_department::Model, parent_id: i64) -> Result<()> {
+        let (path, depth) = Self::child_path(&Self::parent_path(db, parent_id).await?, model.department_id);
+        if let Err(e) = TDepartment::update_many()
+            .col_expr(
+                t_department::Column::Path,
+                Expr::cust_with_values("CONCAT(?, SUBSTRING(`path`, ?))", [Value::from(path.clone()), Value::from(model.path.len() as i64 + 1)]),
+            )
+            .col_expr(t_department::Column::Depth, Expr::col(t_department::Column::Depth).add(depth - model.depth))
+            .filter(t_department::Column::Path.starts_with(Self::indexed_path(model)?))
+            .exec(db)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_department path");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        Ok(())
+    }
+
+    // 上级部门的路径，顶级为 "/"；加锁读取，在事务中调用时并发移动上级部门需等待
+    async fn parent_path<C: ConnectionTrait>(db: &C, parent_id: i64) -> Result<String> {
+        if parent_id == 0 {
+            return Ok("/".to_string());
+        }
+        Ok(Self::find_locked(db, parent_id).await?.path)
+    }
+
+    fn child_path(parent_path: &str, department_id: i64) -> (String, i32) {
+        let path = format!("{}{}/", parent_path, department_id);
+        let depth = Self::path_ids(&path).len() as i32 - 1;
+        (path, depth)
+    }
+
+    // 校验部门负责人：员工需存在且启用，并属于本部门或（新的）上级部门链中的部门
+    async fn check_manager(&self, department_id: i64, parent_id: i64, manager_id: i64) -> Result<()> {
+        if manager_id == 0 {
+            return Ok(());
+        }
+        let employee = TEmployee::find_by_id(manager_id)
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrParams(Some("部门负责人不存在".to_string())))?;
+        if employee.disabled_flag == 1 {
+            return Err(ApiErr::ErrParams(Some("部门负责人账号已禁用".to_string())));
+        }
+
+        let mut allowed = Self::path_ids(&Self::parent_path(&self.conn, parent_id).await?);
+        allowed.push(department_id);
+        if !allowed.contains(&employee.department_id) {
+            return Err(ApiErr::ErrParams(Some("部门负责人必须是本部门或上级部门的员工".to_string())));
+        }
+        Ok(())
+    }
+
+    // 我负责的部门
+    pub async fn managed(&self, employee_id: i64) -> Result<ApiOK<Vec<RespInfo>>> {
+        let models = TDepartment::find()
+            .filter(t_department::Column::ManagerId.eq(employee_id))
+            .order_by(t_department::Column::Depth, Order::Asc)
+            .order_by(t_department::Column::Sort, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        let manager_name = self.employee_names(HashSet::from([employee_id])).await?.remove(&employee_id).unwrap_or_default();
+
+        let list = models
+            .into_iter()
+            .map(|model| RespInfo {
+                department_id: model.department_id,
+                department_name: model.department_name,
+                manager_id: model.manager_id,
+                manager_name: manager_name.clone(),
+                parent_id: model.parent_id,
+                sort: model.sort,
+                create_time: model.create_time,
+                create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 我的直接下属
+    pub async fn direct_reports(&self, employee_id: i64) -> Result<ApiOK<Vec<RespReport>>> {
+        let reports = self.reporting_lines().await?;
+        let mut list: Vec<RespReport> = reports
+            .into_values()
+            .filter(|report| report.manager_id == employee_id)
+            .collect();
+        list.sort_by_key(|report| report.employee_id);
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 以我为根的完整汇报树
+    pub async fn reporting_tree(&self, employee_id: i64) -> Result<ApiOK<Vec<tree::TreeNode<RespReport>>>> {
+        let mut reports = self.reporting_lines().await?;
+        let mut root = reports
+            .remove(&employee_id)
+            .ok_or(ApiErr::ErrNotFound(Some("员工信息不存在".to_string())))?;
+        root.manager_id = 0;
+
+        let mut items = vec![root];
+        items.extend(reports.into_values());
+        let forest = tree::build(items, tree::Orphans::Drop);
+        Ok(ApiOK(Some(forest.roots)))
+    }
+
+    // 所有在职员工的直属上级：从所在部门向上查找第一个有负责人（且不是本人）的部门
+    async fn reporting_lines(&self) -> Result<HashMap<i64, RespReport>> {
+        let departments: HashMap<i64, (i64, i64, String)> = TDepartment::find()
+            .select_only()
+            .column(t_department::Column::DepartmentId)
+            .column(t_department::Column::ParentId)
+            .column(t_department::Column::ManagerId)
+            .column(t_department::Column::DepartmentName)
+            .into_tuple::<(i64, i64, i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .map(|(id, parent_id, manager_id, name)| (id, (parent_id, manager_id, name)))
+            .collect();
+        let employees = TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::EmployeeId)
+            .column(t_employee::Column::Realname)
+            .column(t_employee::Column::DepartmentId)
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .filter(t_employee::Column::DisabledFlag.eq(0))
+            .into_tuple::<(i64, String, i64)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        let active: HashSet<i64> = employees.iter().map(|(id, _, _)| *id).collect();
+
+        let reports = employees
+            .into_iter()
+            .map(|(employee_id, realname, department_id)| {
+                let mut manager_id = 0;
+                let mut current = department_id;
+                let mut visited = HashSet::new();
+                while let Some((parent_id, manager, _)) = departments.get(&current) {
+                    if !visited.insert(current) {
+                        break;
+                    }
+                    // 负责人已离职或禁用时继续向上查找
+                    if *manager != employee_id && active.contains(manager) {
+                        manager_id = *manager;
+                        break;
+                    }
+                    current = *parent_id;
+                }
+                let report = RespReport {
+                    employee_id,
+                    realname,
+                    department_id,
+                    department_name: departments.get(&department_id).map(|d| d.2.clone()).unwrap_or_default(),
+                    manager_id,
+                };
+                (employee_id, report)
+            })
+            .collect();
+        Ok(reports)
+    }
+
+    // 组织架构图的部门数据：负责人姓名与在职人数，with_employees 时附带员工姓名
+    pub async fn org_units(&self, with_employees: bool) -> Result<Vec<OrgUnit>> {
+        let models = TDepartment::find().all(&self.conn).await.map_err(|e| {
+            tracing::error!(error = ?e, "error find t_department");
+            ApiErr::ErrSystem(None)
+        })?;
+        let employees = TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::EmployeeId)
+            .column(t_employee::Column::Realname)
+            .column(t_employee::Column::DepartmentId)
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .filter(t_employee::Column::DisabledFlag.eq(0))
+            .order_by(t_employee::Column::EmployeeId, Order::Asc)
+            .into_tuple::<(i64, String, i64)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let names: HashMap<i64, String> = employees.iter().map(|(id, name, _)| (*id, name.clone())).collect();
+        let mut members: HashMap<i64, Vec<String>> = HashMap::new();
+        for (_, realname, department_id) in employees {
+            members.entry(department_id).or_default().push(realname);
+        }
+
+        Ok(models
+            .into_iter()
+            .map(|model| {
+                let department_members = members.remove(&model.department_id).unwrap_or_default();
+                OrgUnit {
+                    department_id: model.department_id,
+                    department_name: model.department_name,
+                    parent_id: model.parent_id,
+                    sort: model.sort as i64,
+                    manager_id: model.manager_id,
+                    manager_name: names.get(&model.manager_id).cloned().unwrap_or_default(),
+                    headcount: department_members.len(),
+                    total_headcount: 0,
+                    employees: if with_employees { department_members } else { Vec::new() },
+                }
+            })
+            .collect())
+    }
+
+    async fn employee_names(&self, ids: HashSet<i64>) -> Result<HashMap<i64, String>> {
+        Ok(TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::EmployeeId)
+            .column(t_employee::Column::Realname)
+            .filter(t_employee::Column::EmployeeId.is_in(ids))
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect())
+    }
+
+    // 路径为空说明层级索引尚未生成，此时按前缀匹配会命中全部部门
+    fn indexed_path(model: &t_department::Model) -> Result<&str> {
+        if model.path.is_empty() {
+            tracing::error!(department_id = model.department_id, "t_department path is empty");
+            return Err(ApiErr::ErrSystem(Some("部门层级路径未生成，请先执行 department rebuild-path".to_string())));
+        }
+        Ok(&model.path)
+    }
+
+    fn path_ids(path: &str) -> Vec<i64> {
+        path.split('/').filter_map(|id| id.parse::<i64>().ok()).collect()
+    }
+
+    fn path_node(model: t_department::Model) -> RespPathNode {
+        RespPathNode {
+            department_id: model.department_id,
+            department_name: model.department_name,
+            parent_id: model.parent_id,
+            depth: model.depth,
+        }
+    }
+
+    // 按部门名称（含拼音）搜索部门
+    pub async fn search(&self, query: HashMap<String, String>) -> Result<ApiOK<Vec<RespSearch>>> {
+        let keyword = search::normalize(query.get("keyword").map(String::as_str).unwrap_or_default());
+        if keyword.is_empty() {
+            return Ok(ApiOK(Some(Vec::new())));
+        }
+        let pinyin_keyword = search::pinyin_keyword(&keyword);
+
+        // 候选数量有限，先取完全匹配、再取前缀匹配的记录，避免被包含匹配挤出
+        let pinyin_columns = [t_department::Column::NamePinyin, t_department::Column::NameInitials];
+        let name = t_department::Column::DepartmentName;
+        let (mut contains, mut exact, mut prefix) = (
+            Condition::any().add(Expr::col((TDepartment, name)).like(search::like_contains(&keyword))),
+            Condition::any().add(name.eq(&keyword)),
+            Condition::any().add(Expr::col((TDepartment, name)).like(search::like_prefix(&keyword))),
+        );
+        for column in pinyin_columns {
+            contains = contains.add(Expr::col((TDepartment, column)).like(search::like_contains(&pinyin_keyword)));
+            exact = exact.add(column.eq(&pinyin_keyword));
+            prefix = prefix.add(Expr::col((TDepartment, column)).like(search::like_prefix(&pinyin_keyword)));
+        }
+        let models = TDepartment::find()
+            .filter(contains)
+            .order_by(SimpleExpr::Case(Box::new(Expr::case(exact, 0).case(prefix, 1).finally(2))), Order::Asc)
+            .order_by(t_department::Column::DepartmentId, Order::Asc)
+            .limit(search::CANDIDATES)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let mut list: Vec<RespSearch> = models
+            .into_iter()
+            .filter_map(|model| {
+                let matched = search::matches(&model.department_name, &keyword, true)?;
+                Some(RespSearch {
+                    department_id: model.department_id,
+                    department_name: model.department_name,
+                    parent_id: model.parent_id,
+                    kind: matched.kind,
+                    score: matched.score,
+                    highlight: matched.highlight,
+                })
+            })
+            .collect();
+        list.sort_by(|a, b| b.score.cmp(&a.score).then(a.department_id.cmp(&b.department_id)));
+        list.truncate(search::limit(&query));
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 重新计算所有部门名称的拼音，返回更新的数量
+    pub async fn rebuild_pinyin(&self) -> Result<u64> {
+        let models = TDepartment::find().all(&self.conn).await.map_err(|e| {
+            tracing::error!(error = ?e, "error find t_department");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        let mut updated = 0;
+        for model in models {
+            let keys = search::keys(&model.department_name);
+            if keys == (model.name_pinyin, model.name_initials) {
+                continue;
+            }
+            let model = t_department::ActiveModel {
+                department_id: Set(model.department_id),
+                name_pinyin: Set(keys.0),
+                name_initials: Set(keys.1),
+                ..Default::default()
+            };
+            if let Err(e) = TDepartment::update(model).exec(&self.conn).await {
+                tracing::error!(error = ?e, "error update t_department");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn items(&self) -> Result<Vec<tree::Item>> {
+        let department_list = TDepartment::find()
                 .select_only()
                 .column(t_department::Column::DepartmentId)
                 .column(t_department::Column::DepartmentName)
                 .column(t_department::Column::ParentId)
+                .column(t_department::Column::Sort)
+                .into_tuple::<(i64, String, i64, i32)>()
                 .all(&self.conn)
                 .await
                 .map_err(|e| {
                     tracing::error!(error = ?e, "error find t_department");
                     ApiErr::ErrSystem(None)
                 })?;
-    
-        let mut list = Vec::new();
-        for department in &department_list {
-            list.push(RespSelect {
-                department_id: department.department_id,
-                department_name: department.department_name.clone(),
-                parent_id: department.parent_id,
-            });
-        }
-    
-        let tuple_list = list.iter().map(|item| (item.department_id,item.department_name.clone(),Some(item.parent_id))).collect::<Vec<_>>();
-        let tuple_node: Option<Vec<tree::TreeNode>> = tree::build_tree(tuple_list);
-        Ok(ApiOK(tuple_node))
+
+        Ok(department_list
+            .into_iter()
+            .map(|(id, name, parent_id, sort)| tree::Item { id, name, parent_id, sort: sort as i64 })
+            .collect())
     }
 
     /** 获取列表 */
@@ -135,12 +683,14 @@ impl DepartmentRepository {
             total,
             list: (Vec::with_capacity(models.len())),
         };
+        let managers = self.employee_names(models.iter().map(|model| model.manager_id).collect()).await?;
 
         for model in models {
             let info = RespInfo {
                 department_id: model.department_id,
                 department_name: model.department_name,
                 manager_id: model.manager_id,
+                manager_name: managers.get(&model.manager_id).cloned().unwrap_or_default(),
                 parent_id: model.parent_id,
                 sort: model.sort,
                 create_time: model.create_time,
@@ -167,10 +717,12 @@ impl DepartmentRepository {
             })?
             .ok_or(ApiErr::ErrNotFound(Some("部门信息不存在".to_string())))?;
 
+    let manager_name = self.employee_names(HashSet::from([model.manager_id])).await?.remove(&model.manager_id);
     let mut resp = RespInfo {
             department_id: model.department_id,
             department_name: model.department_name,
             manager_id: model.manager_id,
+            manager_name: manager_name.unwrap_or_default(),
             parent_id: model.parent_id,
             sort: model.sort,
             create_time: model.create_time,
@@ -182,10 +734,15 @@ impl DepartmentRepository {
 
     // 修改方法
     pub async fn update(&self, req: UpdateInfo) -> Result<ApiOK<()>> {
+        self.check_manager(req.deptid, req.parentid, req.managerid).await?;
+
         let now = xtime::now(offset!(+8)).unix_timestamp();
+        let (name_pinyin, name_initials) = search::keys(&req.deptname);
         let model = t_department::ActiveModel {
             department_id: Set(req.deptid),
             department_name: Set(req.deptname),  
+            name_pinyin: Set(name_pinyin),
+            name_initials: Set(name_initials),
             sort: Set(req.sort),
             manager_id: Set(req.managerid),
             parent_id: Set(req.parentid),
@@ -193,15 +750,339 @@ impl DepartmentRepository {
             ..Default::default()
         };
 
+        // 在事务中加锁读取本部门与上级部门后校验循环，并发移动时串行执行
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        let current = Self::find_locked(&txn, req.deptid).await?;
+        Self::check_parent(&txn, req.deptid, req.parentid).await?;
         if let Err(e) = TDepartment::update(model)
-        .exec(&self.conn)
+        .exec(&txn)
         .await {
             tracing::error!(error = ?e, "error update t_department");
             return Err(ApiErr::ErrSystem(None));
         }
+        if current.parent_id != req.parentid {
+            self.move_path(&txn, &current, req.parentid).await?;
+            // 上级部门链变化，子树内各部门的负责人需重新校验
+            check_managers(&txn, subtree_in(&txn, req.deptid).await?).await?;
+        }
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
         Ok(ApiOK(None))
     }
 
+    // 移动部门到新的上级下，并重写新上级下各部门的排序
+    pub async fn move_department(&self, req: ReqMove) -> Result<ApiOK<()>> {
+        // 在事务中加锁读取本部门与上级部门后校验循环，并发移动时串行执行
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        let model = Self::find_locked(&txn, req.department_id).await?;
+        Self::check_parent(&txn, req.department_id, req.parent_id).await?;
+
+        let mut ordered: Vec<i64> = Self::children(&txn, req.parent_id)
+            .await?
+            .into_iter()
+            .map(|(department_id, _)| department_id)
+            .filter(|department_id| *department_id != req.department_id)
+            .collect();
+        let index = req.index.unwrap_or(ordered.len()).min(ordered.len());
+        ordered.insert(index, req.department_id);
+
+        if model.parent_id != req.parent_id {
+            self.set_parent(&txn, &model, req.parent_id).await?;
+            // 上级部门链变化，子树内各部门的负责人需重新校验
+            check_managers(&txn, subtree_in(&txn, req.department_id).await?).await?;
+        }
+        Self::write_sort(&txn, &ordered).await?;
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        Ok(ApiOK(None))
+    }
+
+    // 合并部门：在同一事务中把源部门的下级部门与员工移入目标部门，记录员工履历后删除源部门
+    // 预览同样在事务中读取，返回前不提交
+    pub async fn merge(&self, req: ReqMerge, operator_id: i64) -> Result<ApiOK<RespMerge>> {
+        if req.source_id == req.target_id {
+            return Err(ApiErr::ErrParams(Some("源部门与目标部门不能相同".to_string())));
+        }
+
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        // 加锁读取，合并期间其他事务不能向源部门移入部门或员工
+        let source = Self::find_locked(&txn, req.source_id).await?;
+        let target = Self::find_locked(&txn, req.target_id).await?;
+        if Self::path_ids(&target.path).contains(&source.department_id) {
+            return Err(ApiErr::ErrParams(Some("目标部门不能是源部门的下级部门".to_string())));
+        }
+        let change = Change::new(
+            operator_id,
+            req.effective_date.as_deref(),
+            Some(req.reason.clone().unwrap_or_else(|| format!("部门合并：{} → {}", source.department_name, target.department_name))),
+        )?;
+
+        let children = TDepartment::find()
+            .filter(t_department::Column::ParentId.eq(source.department_id))
+            .order_by(t_department::Column::Sort, Order::Asc)
+            .order_by(t_department::Column::DepartmentId, Order::Asc)
+            .lock_exclusive()
+            .all(&txn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        let employees = TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::EmployeeId)
+            .column(t_employee::Column::Realname)
+            .column(t_employee::Column::DeletedFlag)
+            .filter(t_employee::Column::DepartmentId.eq(source.department_id))
+            .order_by(t_employee::Column::EmployeeId, Order::Asc)
+            .lock_exclusive()
+            .into_tuple::<(i64, String, u8)>()
+            .all(&txn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        let (employees, deleted_employees): (Vec<_>, Vec<_>) = employees
+            .into_iter()
+            .map(|(employee_id, realname, deleted_flag)| (RespMergeEmployee { employee_id, realname }, deleted_flag))
+            .partition(|(_, deleted_flag)| *deleted_flag == 0);
+
+        let resp = RespMerge {
+            preview: req.preview,
+            source_id: source.department_id,
+            source_name: source.department_name.clone(),
+            target_id: target.department_id,
+            target_name: target.department_name.clone(),
+            children: children.iter().cloned().map(Self::path_node).collect(),
+            employees: employees.into_iter().map(|(employee, _)| employee).collect(),
+            deleted_employees: deleted_employees.into_iter().map(|(employee, _)| employee).collect(),
+        };
+        if req.preview {
+            return Ok(ApiOK(Some(resp)));
+        }
+
+        // 下级部门排在目标部门原有下级之后
+        let mut ordered: Vec<i64> = TDepartment::find()
+            .select_only()
+            .column(t_department::Column::DepartmentId)
+            .filter(t_department::Column::ParentId.eq(target.department_id))
+            .order_by(t_department::Column::Sort, Order::Asc)
+            .order_by(t_department::Column::DepartmentId, Order::Asc)
+            .into_tuple::<i64>()
+            .all(&txn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        ordered.extend(children.iter().map(|child| child.department_id));
+
+        for child in &children {
+            self.set_parent(&txn, child, target.department_id).await?;
+        }
+        Self::write_sort(&txn, &ordered).await?;
+
+        let employee_ids: Vec<i64> = resp.employees.iter().map(|employee| employee.employee_id).collect();
+        history::move_department(&txn, &employee_ids, target.department_id, &change).await?;
+        // 已删除员工不在职，不记录履历，只改所属部门，避免指向已删除的部门
+        let deleted_ids: Vec<i64> = resp.deleted_employees.iter().map(|employee| employee.employee_id).collect();
+        if !deleted_ids.is_empty() {
+            let now = xtime::now(offset!(+8)).unix_timestamp();
+            if let Err(e) = TEmployee::update_many()
+                .col_expr(t_employee::Column::DepartmentId, Expr::value(target.department_id))
+                .col_expr(t_employee::Column::UpdateTime, Expr::value(now))
+                .filter(t_employee::Column::EmployeeId.is_in(deleted_ids.clone()))
+                .exec(&txn)
+                .await
+            {
+                tracing::error!(error = ?e, "error update t_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+        // 移入的下级部门换了上级链，源部门员工换了部门，目标部门及其子树的负责人需重新校验
+        check_managers(&txn, subtree_in(&txn, target.department_id).await?).await?;
+
+        if let Err(e) = TDepartment::delete_by_id(source.department_id).exec(&txn).await {
+            tracing::error!(error = ?e, "error delete t_department");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let operator_name = self.employee_names(HashSet::from([operator_id])).await?.remove(&operator_id);
+        let log = t_operate_log::ActiveModel {
+            operate_user_id: Set(operator_id),
+            operate_user_type: Set(0),
+            operate_user_name: Set(operator_name.unwrap_or_default()),
+            module: Set(Some("部门管理".to_string())),
+            content: Set(Some(format!(
+                "部门合并：{} → {}，移动下级部门 {} 个、员工 {} 人（另有已删除员工 {} 人）",
+                source.department_name,
+                target.department_name,
+                resp.children.len(),
+                resp.employees.len(),
+                resp.deleted_employees.len(),
+            ))),
+            param: Set(Some(
+                serde_json::json!({
+                    "source_id": source.department_id,
+                    "target_id": target.department_id,
+                    "children": children.iter().map(|child| child.department_id).collect::<Vec<_>>(),
+                    "employees": employee_ids,
+                    "deleted_employees": deleted_ids,
+                    "reason": change.reason,
+                })
+                .to_string(),
+            )),
+            success_flag: Set(Some(1)),
+            create_time: Set(now),
+            update_time: Set(now),
+            ..Default::default()
+        };
+        if let Err(e) = TOperateLog::insert(log).exec(&txn).await {
+            tracing::error!(error = ?e, "error insert t_operate_log");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        Ok(ApiOK(Some(resp)))
+    }
+
+    // 修改上级部门并同步子树路径
+    async fn set_parent<C: ConnectionTrait>(&self, db: &C, model: &t_department::Model, parent_id: i64) -> Result<()> {
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let update = t_department::ActiveModel {
+            department_id: Set(model.department_id),
+            parent_id: Set(parent_id),
+            update_time: Set(now),
+            ..Default::default()
+        };
+        if let Err(e) = TDepartment::update(update).exec(db).await {
+            tracing::error!(error = ?e, "error update t_department");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        self.move_path(db, model, parent_id).await
+    }
+
+    // 同级部门排序，department_ids 必须恰好是该上级下的全部部门
+    pub async fn reorder(&self, req: ReqReorder) -> Result<ApiOK<()>> {
+        let mut current: Vec<i64> = Self::children(&self.conn, req.parent_id)
+            .await?
+            .into_iter()
+            .map(|(department_id, _)| department_id)
+            .collect();
+        let mut requested = req.department_ids.clone();
+        current.sort_unstable();
+        requested.sort_unstable();
+        if current != requested {
+            return Err(ApiErr::ErrParams(Some("部门列表与上级部门的下级部门不一致，请刷新后重试".to_string())));
+        }
+
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        Self::write_sort(&txn, &req.department_ids).await?;
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        Ok(ApiOK(None))
+    }
+
+    // 按顺序将 sort 重写为 1、2、3…
+    async fn write_sort<C: ConnectionTrait>(db: &C, ordered: &[i64]) -> Result<()> {
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        for (index, department_id) in ordered.iter().enumerate() {
+            let model = t_department::ActiveModel {
+                department_id: Set(*department_id),
+                sort: Set(index as i32 + 1),
+                update_time: Set(now),
+                ..Default::default()
+            };
+            if let Err(e) = TDepartment::update(model).exec(db).await {
+                tracing::error!(error = ?e, "error update t_department");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+        Ok(())
+    }
+
+    // 上级部门下的 (部门ID, 排序)，按 sort、部门ID 排列
+    async fn children<C: ConnectionTrait>(db: &C, parent_id: i64) -> Result<Vec<(i64, i32)>> {
+        TDepartment::find()
+            .select_only()
+            .column(t_department::Column::DepartmentId)
+            .column(t_department::Column::Sort)
+            .filter(t_department::Column::ParentId.eq(parent_id))
+            .order_by(t_department::Column::Sort, Order::Asc)
+            .order_by(t_department::Column::DepartmentId, Order::Asc)
+            .into_tuple::<(i64, i32)>()
+            .all(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })
+    }
+
+    async fn find_locked<C: ConnectionTrait>(db: &C, department_id: i64) -> Result<t_department::Model> {
+        TDepartment::find_by_id(department_id)
+            .lock_exclusive()
+            .one(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("部门信息不存在".to_string())))
+    }
+
+    async fn find_department(&self, department_id: i64) -> Result<t_department::Model> {
+        TDepartment::find_by_id(department_id)
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("部门信息不存在".to_string())))
+    }
+
+    // 校验上级部门：不能是自身或自身的下级，避免出现循环；上级部门行加锁读取
+    async fn check_parent<C: ConnectionTrait>(db: &C, department_id: i64, parent_id: i64) -> Result<()> {
+        if parent_id == 0 {
+            return Ok(());
+        }
+        if parent_id == department_id {
+            return Err(ApiErr::ErrParams(Some("上级部门不能是自身".to_string())));
+        }
+
+        let parent = Self::find_locked(db, parent_id)
+            .await
+            .map_err(|_| ApiErr::ErrParams(Some("上级部门不存在".to_string())))?;
+        if department_id != 0 && Self::path_ids(&parent.path).contains(&department_id) {
+            return Err(ApiErr::ErrParams(Some("上级部门不能是自身的下级部门".to_string())));
+        }
+        Ok(())
+    }
+
     // 删除部门
     pub async fn delete(&self, department_id: i64) -> Result<ApiOK<()>> {
         //判断是否有子部门
@@ -221,6 +1102,7 @@ impl DepartmentRepository {
         //判断删除部门时，该部门下是否有用户
         let  employee_count = TEmployee::find()
             .filter(t_employee::Column::DepartmentId.eq(department_id))
+            .filter(t_employee::Column::DeletedFlag.eq(0))
             .count(&self.conn)
             .await
             .map_err(|e| {
@@ -0,0 +1,352 @@
+use std::collections::{HashMap, HashSet};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Order,
+    QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use sea_orm::prelude::Expr;
+use time::macros::offset;
+use crate::domain::entities::{
+    t_department, prelude::TDepartment,
+    t_employee, prelude::TEmployee,
+    t_employee_history, prelude::TEmployeeHistory,
+    t_position, prelude::TPosition,
+};
+use crate::application::dto::employee_dto::{RespHistory, RespHistoryMember};
+use crate::common::result::response::{ApiErr, ApiOK, Result};
+use crate::common::xtime;
+use crate::infrastructure::persistence::database as db;
+use super::department_repository::DepartmentRepository;
+
+/// 变动类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeType {
+    /// 入职（新增、导入）
+    Hire,
+    /// 调动部门（可同时调整职位）
+    Transfer,
+    /// 仅调整职位
+    Position,
+    /// 恢复已删除员工
+    Restore,
+    /// 离职或删除，部门与职位记录离开时的任职
+    Leave,
+}
+
+impl ChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hire => "hire",
+            Self::Transfer => "transfer",
+            Self::Position => "position",
+            Self::Restore => "restore",
+            Self::Leave => "leave",
+        }
+    }
+
+    /// 根据变动前后的部门与职位判断类型，没有变动时返回 None
+    pub fn detect(prev: (i64, i64), current: (i64, i64)) -> Option<Self> {
+        if prev.0 != current.0 {
+            Some(Self::Transfer)
+        } else if prev.1 != current.1 {
+            Some(Self::Position)
+        } else {
+            None
+        }
+    }
+}
+
+/// 变动的生效时间、操作人与原因
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub effective_time: i64,
+    pub operator_id: i64,
+    pub reason: String,
+}
+
+impl Change {
+    /// 生效日期格式为 2006-01-02，为空时取当前时间
+    pub fn new(operator_id: i64, effective_date: Option<&str>, reason: Option<String>) -> Result<Self> {
+        let effective_time = match effective_date.filter(|v| !v.is_empty()) {
+            Some(date) => xtime::to_timestamp(xtime::DATETIME, &format!("{} 00:00:00", date), offset!(+8))
+                .map_err(|_| ApiErr::ErrParams(Some("生效日期格式错误".to_string())))?,
+            None => xtime::now(offset!(+8)).unix_timestamp(),
+        };
+        Ok(Self {
+            effective_time,
+            operator_id,
+            reason: reason.unwrap_or_default(),
+        })
+    }
+}
+
+/// 一条履历：员工从 prev（部门, 职位）变为 current
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub employee_id: i64,
+    pub change_type: ChangeType,
+    pub prev: (i64, i64),
+    pub current: (i64, i64),
+}
+
+/// 写入履历，应与员工数据的修改处于同一事务
+pub async fn record<C: ConnectionTrait>(db: &C, entries: Vec<Entry>, change: &Change) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let now = xtime::now(offset!(+8)).unix_timestamp();
+    let models = entries.into_iter().map(|entry| t_employee_history::ActiveModel {
+        employee_id: Set(entry.employee_id),
+        change_type: Set(entry.change_type.as_str().to_string()),
+        prev_department_id: Set(entry.prev.0),
+        prev_position_id: Set(entry.prev.1),
+        department_id: Set(entry.current.0),
+        position_id: Set(entry.current.1),
+        effective_time: Set(change.effective_time),
+        operator_id: Set(change.operator_id),
+        reason: Set(change.reason.clone()),
+        create_time: Set(now),
+        ..Default::default()
+    });
+    if let Err(e) = TEmployeeHistory::insert_many(models).exec(db).await {
+        tracing::error!(error = ?e, "error insert t_employee_history");
+        return Err(ApiErr::ErrSystem(None));
+    }
+    Ok(())
+}
+
+/// 批量调整员工部门并记录履历，已在目标部门的员工忽略，返回实际调动的员工ID
+pub async fn move_department<C: ConnectionTrait>(
+    db: &C,
+    employee_ids: &[i64],
+    department_id: i64,
+    change: &Change,
+) -> Result<Vec<i64>> {
+    let employees = TEmployee::find()
+        .select_only()
+        .column(t_employee::Column::EmployeeId)
+        .column(t_employee::Column::DepartmentId)
+        .column(t_employee::Column::PositionId)
+        .filter(t_employee::Column::EmployeeId.is_in(employee_ids.iter().copied()))
+        .filter(t_employee::Column::DepartmentId.ne(department_id))
+        .filter(t_employee::Column::DeletedFlag.eq(0))
+        .into_tuple::<(i64, i64, i64)>()
+        .all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_employee");
+            ApiErr::ErrSystem(None)
+        })?;
+    if employees.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let moved: Vec<i64> = employees.iter().map(|(employee_id, _, _)| *employee_id).collect();
+    let now = xtime::now(offset!(+8)).unix_timestamp();
+    if let Err(e) = TEmployee::update_many()
+        .col_expr(t_employee::Column::DepartmentId, Expr::value(department_id))
+        .col_expr(t_employee::Column::UpdateTime, Expr::value(now))
+        .filter(t_employee::Column::EmployeeId.is_in(moved.clone()))
+        .exec(db)
+        .await
+    {
+        tracing::error!(error = ?e, "error update t_employee");
+        return Err(ApiErr::ErrSystem(None));
+    }
+
+    let entries = employees
+        .into_iter()
+        .map(|(employee_id, prev_department_id, position_id)| Entry {
+            employee_id,
+            change_type: ChangeType::Transfer,
+            prev: (prev_department_id, position_id),
+            current: (department_id, position_id),
+        })
+        .collect();
+    record(db, entries, change).await?;
+    Ok(moved)
+}
+
+pub struct EmployeeHistoryRepository {
+    conn: DatabaseConnection,
+    department: DepartmentRepository,
+}
+
+impl EmployeeHistoryRepository {
+    pub fn new() -> Self {
+        Self {
+            conn: db::conn().clone(),
+            department: DepartmentRepository::new(),
+        }
+    }
+
+    // 员工任职履历，按生效时间倒序
+    pub async fn timeline(&self, employee_id: i64) -> Result<ApiOK<Vec<RespHistory>>> {
+        let models = TEmployeeHistory::find()
+            .filter(t_employee_history::Column::EmployeeId.eq(employee_id))
+            .order_by(t_employee_history::Column::EffectiveTime, Order::Desc)
+            .order_by(t_employee_history::Column::HistoryId, Order::Desc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee_history");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let departments = self
+            .department_names(models.iter().flat_map(|m| [m.department_id, m.prev_department_id]).collect())
+            .await?;
+        let positions = self
+            .position_names(models.iter().flat_map(|m| [m.position_id, m.prev_position_id]).collect())
+            .await?;
+        let operators = self.employee_names(models.iter().map(|m| m.operator_id).collect()).await?;
+        let name = |names: &HashMap<i64, String>, id: i64| names.get(&id).cloned().unwrap_or_default();
+
+        let list = models
+            .into_iter()
+            .map(|model| RespHistory {
+                history_id: model.history_id,
+                change_type: model.change_type,
+                prev_department_id: model.prev_department_id,
+                prev_department_name: name(&departments, model.prev_department_id),
+                prev_position_id: model.prev_position_id,
+                prev_position_name: name(&positions, model.prev_position_id),
+                department_id: model.department_id,
+                department_name: name(&departments, model.department_id),
+                position_id: model.position_id,
+                position_name: name(&positions, model.position_id),
+                effective_time: model.effective_time,
+                effective_time_str: xtime::to_string(xtime::DATE, model.effective_time, offset!(+8))
+                    .unwrap_or_default(),
+                operator_id: model.operator_id,
+                operator_name: name(&operators, model.operator_id),
+                reason: model.reason,
+                create_time: model.create_time,
+                create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 某一时间点在部门任职的员工，include_sub 时包含下级部门
+    pub async fn members(&self, department_id: i64, time: i64, include_sub: bool) -> Result<ApiOK<Vec<RespHistoryMember>>> {
+        let department_ids = if include_sub {
+            self.department.subtree_ids(department_id).await?
+        } else {
+            vec![department_id]
+        };
+
+        // 先找出在该时间点之前进入过这些部门的员工，再取每人在该时间点的最后一条履历
+        let candidates: Vec<i64> = TEmployeeHistory::find()
+            .select_only()
+            .column(t_employee_history::Column::EmployeeId)
+            .distinct()
+            .filter(t_employee_history::Column::DepartmentId.is_in(department_ids.clone()))
+            .filter(t_employee_history::Column::EffectiveTime.lte(time))
+            .into_tuple::<i64>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee_history");
+                ApiErr::ErrSystem(None)
+            })?;
+        if candidates.is_empty() {
+            return Ok(ApiOK(Some(Vec::new())));
+        }
+
+        let models = TEmployeeHistory::find()
+            .filter(t_employee_history::Column::EmployeeId.is_in(candidates))
+            .filter(t_employee_history::Column::EffectiveTime.lte(time))
+            .order_by(t_employee_history::Column::EffectiveTime, Order::Asc)
+            .order_by(t_employee_history::Column::HistoryId, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee_history");
+                ApiErr::ErrSystem(None)
+            })?;
+        let mut latest: HashMap<i64, t_employee_history::Model> = HashMap::new();
+        for model in models {
+            latest.insert(model.employee_id, model);
+        }
+        // 最后一条为离职的员工在该时间点已不在职
+        let department_set: HashSet<i64> = department_ids.into_iter().collect();
+        let mut members: Vec<t_employee_history::Model> = latest
+            .into_values()
+            .filter(|model| model.change_type != ChangeType::Leave.as_str())
+            .filter(|model| department_set.contains(&model.department_id))
+            .collect();
+        members.sort_by_key(|model| model.employee_id);
+
+        let departments = self.department_names(members.iter().map(|m| m.department_id).collect()).await?;
+        let positions = self.position_names(members.iter().map(|m| m.position_id).collect()).await?;
+        let employees = self.employee_names(members.iter().map(|m| m.employee_id).collect()).await?;
+        let name = |names: &HashMap<i64, String>, id: i64| names.get(&id).cloned().unwrap_or_default();
+
+        let list = members
+            .into_iter()
+            .map(|model| RespHistoryMember {
+                employee_id: model.employee_id,
+                realname: name(&employees, model.employee_id),
+                department_id: model.department_id,
+                department_name: name(&departments, model.department_id),
+                position_id: model.position_id,
+                position_name: name(&positions, model.position_id),
+                since: model.effective_time,
+                since_str: xtime::to_string(xtime::DATE, model.effective_time, offset!(+8)).unwrap_or_default(),
+            })
+            .collect();
+        Ok(ApiOK(Some(list)))
+    }
+
+    async fn department_names(&self, ids: HashSet<i64>) -> Result<HashMap<i64, String>> {
+        Ok(TDepartment::find()
+            .select_only()
+            .column(t_department::Column::DepartmentId)
+            .column(t_department::Column::DepartmentName)
+            .filter(t_department::Column::DepartmentId.is_in(ids))
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect())
+    }
+
+    async fn position_names(&self, ids: HashSet<i64>) -> Result<HashMap<i64, String>> {
+        Ok(TPosition::find()
+            .select_only()
+            .column(t_position::Column::PositionId)
+            .column(t_position::Column::PositionName)
+            .filter(t_position::Column::PositionId.is_in(ids))
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_position");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect())
+    }
+
+    async fn employee_names(&self, ids: HashSet<i64>) -> Result<HashMap<i64, String>> {
+        Ok(TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::EmployeeId)
+            .column(t_employee::Column::Realname)
+            .filter(t_employee::Column::EmployeeId.is_in(ids))
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect())
+    }
+}
@@ -1,42 +1,99 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use sea_orm::{
     ColumnTrait, DatabaseConnection, EntityTrait, Order,        
     PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
-    Condition,
+    Condition, TransactionTrait, Select, FromQueryResult, JoinType,
+    SelectModel, Selector,
 };
 use sea_orm::prelude::Expr;
+use sea_orm::sea_query::SimpleExpr;
 use crate::domain::entities::{
     t_department, prelude::TDepartment,
-    t_employee, prelude::TEmployee
+    t_employee, prelude::TEmployee,
+    t_role_employee, prelude::TRoleEmployee,
+    t_position, prelude::TPosition,
+    t_role, prelude::TRole,
+    t_employee_offboard, prelude::TEmployeeOffboard,
 };
 use crate::application::dto::employee_dto::{ReqCreate, UpdateInfo, RespInfo, 
-    RespList, RespSelectOption, RespEmpInfo, RespDeptInfo};
+    RespList, RespSelectOption, RespEmpInfo, RespDeptInfo,
+    ImportRow, RespImportError, RespImportReport, RespExportRow, RespProfile, ReqProfile, RespSearch,
+    ReqOffboard, RespOffboard, RespOffboardRole, RespOffboardDepartment, validate_phone};
 use crate::common::result::response::{ApiErr, ApiOK, Result};
 use time::macros::offset;
+use validator::ValidateEmail;
 use crate::infrastructure::persistence::database as db;
 use crate::common::crypto::hash::Crypto;
 use crate::common::{
-    xtime, utils
+    config, xtime, utils, search
 };
+use crate::infrastructure::security::permission;
+use super::department_repository::{DepartmentRepository, check_managed_by, check_managers};
+use super::employee_history_repository::{self as history, Change, ChangeType, Entry};
+use super::permission_repository::active_role_employee;
+use super::role_repository::{check_admin_remains, holds_admin};
 
 
 
 
 
+/// 员工及其部门、职位名称的联表查询结果
+#[derive(Debug, FromQueryResult)]
+struct EmployeeRow {
+    employee_id: i64,
+    login_name: String,
+    realname: String,
+    avatar: String,
+    gender: u8,
+    phone: String,
+    email: String,
+    department_id: i64,
+    position_id: i64,
+    disabled_flag: u8,
+    create_time: i64,
+    department_name: Option<String>,
+    position_name: Option<String>,
+}
+
+impl EmployeeRow {
+    fn into_info(self, role_names: Vec<String>) -> RespInfo {
+        RespInfo {
+            employee_id: self.employee_id,
+            login_name: self.login_name,
+            realname: self.realname,
+            avatar: self.avatar,
+            phone: self.phone,
+            email: self.email,
+            gender: self.gender,
+            disabled_flag: self.disabled_flag,
+            position_id: self.position_id,
+            department_id: self.department_id,
+            department_name: self.department_name.unwrap_or_default(),
+            position_name: self.position_name.unwrap_or_default(),
+            role_names,
+            create_time: self.create_time,
+            create_time_str: xtime::to_string(xtime::DATETIME, self.create_time, offset!(+8))
+                .unwrap_or_default(),
+        }
+    }
+}
+
 pub struct EmployeeRepository {
-    conn: DatabaseConnection
+    conn: DatabaseConnection,
+    department: DepartmentRepository,
 }
 
 impl EmployeeRepository {
     pub fn new() -> Self {
         Self {
-            conn: db::conn().clone()
+            conn: db::conn().clone(),
+            department: DepartmentRepository::new(),
         }
     }
 
 
-    pub async fn create(&self, req: ReqCreate) -> Result<ApiOK<()>> {
-        // 验证登录名是否已存在
+    pub async fn create(&self, req: ReqCreate, operator_id: i64) -> Result<ApiOK<()>> {
+        // 验证登录名是否已存在，已删除员工的登录名与手机号仍被占用，以便恢复
         let login_name_count = TEmployee::find()
             .filter(t_employee::Column::LoginName.eq(req.login_name.clone()))
             .count(&self.conn)
@@ -62,10 +119,14 @@ impl EmployeeRepository {
         if phone_count > 0 {
             return Err(ApiErr::ErrPerm(Some("手机号码已重复".to_string())));
         }
+        self.check_position(req.position_id).await?;
     
         let now = xtime::now(offset!(+8)).unix_timestamp();
+        let (name_pinyin, name_initials) = search::keys(&req.realname);
         let model = t_employee::ActiveModel {
             realname: Set(req.realname),
+            name_pinyin: Set(name_pinyin),
+            name_initials: Set(name_initials),
             phone: Set(req.phone),
             department_id: Set(req.department_id),
             login_name: Set(req.login_name.clone()),
@@ -78,12 +139,29 @@ impl EmployeeRepository {
             ..Default::default()
         };
     
-        if let Err(e) = TEmployee::insert(model)
-            .exec(&self.conn)
-            .await{
+        let assignment = (req.department_id, req.position_id);
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        let result = TEmployee::insert(model)
+            .exec(&txn)
+            .await
+            .map_err(|e| {
                 tracing::error!(error = ?e, "error insert t_employee");
-                return Err(ApiErr::ErrSystem(None));
-            }
+                ApiErr::ErrSystem(None)
+            })?;
+        let entry = Entry {
+            employee_id: result.last_insert_id,
+            change_type: ChangeType::Hire,
+            prev: (0, 0),
+            current: assignment,
+        };
+        history::record(&txn, vec![entry], &Change::new(operator_id, None, None)?).await?;
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
     
         Ok(ApiOK(None))     
     }
@@ -91,28 +169,16 @@ impl EmployeeRepository {
 
 
     pub async fn list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
-        let mut builder = TEmployee::find();
-        if let Some(disabled_flag) = query.get("disabled_flag") {
-            if disabled_flag == "1" {
-                builder = builder.filter(t_employee::Column::DisabledFlag.eq(1));
-            } else {
-                builder = builder.filter(t_employee::Column::DisabledFlag.eq(0));
-            }
-        }
-    
-        if let Some(login_name) = query.get("login_name") {
-            if !login_name.is_empty() {
-                builder = builder.filter(t_employee::Column::LoginName.contains(login_name));
-            }
-        }
-    
-        if let Some(phone) = query.get("phone") {
-            if !phone.is_empty() {
-                builder = builder.filter(t_employee::Column::Phone.contains(phone));  
-            }
-        }
-    
-    
+        self.page(query, 0).await
+    }
+
+    // 已删除员工列表
+    pub async fn deleted_list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
+        self.page(query, 1).await
+    }
+
+    async fn page(&self, query: HashMap<String, String>, deleted_flag: u8) -> Result<ApiOK<RespList>> {
+        let builder = self.filter_query(&query, deleted_flag).await?;
     
         let mut total: i64 = 0;
         let pagination = utils::Pagination::from_query(&query).unwrap();
@@ -134,37 +200,30 @@ impl EmployeeRepository {
                 .unwrap_or_default();
         }
     
-        let models = builder
-            .order_by(t_employee::Column::EmployeeId, Order::Desc)
-            .offset(offset)
-            .limit(limit)
-            .all(&self.conn)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = ?e, "error find t_employee");
-                ApiErr::ErrSystem(None)
-            })?;
-        let mut resp = RespList {
+        let rows = Self::with_names(
+            builder
+                .order_by(t_employee::Column::EmployeeId, Order::Desc)
+                .offset(offset)
+                .limit(limit),
+        )
+        .all(&self.conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_employee");
+            ApiErr::ErrSystem(None)
+        })?;
+        let mut role_names = self.role_names(rows.iter().map(|row| row.employee_id).collect()).await?;
+
+        let resp = RespList {
             total,
-            list: (Vec::with_capacity(models.len())),
+            list: rows
+                .into_iter()
+                .map(|row| {
+                    let roles = role_names.remove(&row.employee_id).unwrap_or_default();
+                    row.into_info(roles)
+                })
+                .collect(),
         };
-        for model in models {
-            let info = RespInfo {
-                employee_id: model.employee_id,
-                login_name: model.login_name,
-                realname: model.realname,
-                phone: model.phone,
-                email: model.email,
-                gender: model.gender,
-                disabled_flag: model.disabled_flag,
-                position_id: model.position_id,
-                department_id: model.department_id,
-                create_time: model.create_time,
-                create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
-                .unwrap_or_default(),
-            };
-            resp.list.push(info);
-        }
     
         Ok(ApiOK(Some(resp)))
     
@@ -172,39 +231,31 @@ impl EmployeeRepository {
     
     
     pub async fn info(&self, employee_id: i64) -> Result<ApiOK<RespInfo>> {
-        let model = TEmployee::find_by_id(employee_id)
-            .one(&self.conn)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = ?e, "error find t_employee");
-                ApiErr::ErrSystem(None)
-            })?
-            .ok_or(ApiErr::ErrNotFound(Some("员工信息不存在".to_string())))?;
-    
-    
-        let mut resp = RespInfo {
-            employee_id: model.employee_id,
-            login_name: model.login_name,
-            realname: model.realname,
-            gender: model.gender,
-            phone: model.phone,
-            email: model.email,
-            disabled_flag: model.disabled_flag,
-            position_id: model.position_id,
-            department_id: model.department_id,
-            create_time: model.create_time,
-            create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
-            .unwrap_or_default(),
-        };
-        Ok(ApiOK(Some(resp)))
+        let row = Self::with_names(
+            TEmployee::find_by_id(employee_id).filter(t_employee::Column::DeletedFlag.eq(0)),
+        )
+        .one(&self.conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_employee");
+            ApiErr::ErrSystem(None)
+        })?
+        .ok_or(ApiErr::ErrNotFound(Some("员工信息不存在".to_string())))?;
+
+        let roles = self.role_names(vec![employee_id]).await?.remove(&employee_id).unwrap_or_default();
+        Ok(ApiOK(Some(row.into_info(roles))))
     }
 
 
 
-    pub async fn update(&self, req: UpdateInfo) -> Result<ApiOK<()>> {
+    pub async fn update(&self, req: UpdateInfo, operator_id: i64) -> Result<ApiOK<()>> {
+        let current = self.find_employee(req.employee_id, 0).await?;
+        let change = Change::new(operator_id, req.effective_date.as_deref(), req.reason.clone())?;
+
         /* 判断登录名或者手机号是否重复*/
         let count = TEmployee::find()
         .filter(Condition::any().add(t_employee::Column::LoginName.eq(req.login_name.clone())).add(t_employee::Column::Phone.eq(req.phone.clone())))
+        .filter(t_employee::Column::EmployeeId.ne(req.employee_id))
         .count(&self.conn)
         .await
         .map_err(|e| {
@@ -215,12 +266,20 @@ impl EmployeeRepository {
         if count > 0 {
           return Err(ApiErr::ErrPerm(Some("登录名称或手机号码重复".to_string())));
       }
+      if req.position_id != current.position_id {
+          self.check_position(req.position_id).await?;
+      }
   
       let now = xtime::now(offset!(+8)).unix_timestamp();
+      let (name_pinyin, name_initials) = search::keys(&req.realname);
+      let prev = (current.department_id, current.position_id);
+      let assignment = (req.department_id, req.position_id);
       let model = t_employee::ActiveModel {
           employee_id: Set(req.employee_id),
           login_name: Set(req.login_name),
           realname: Set(req.realname),
+          name_pinyin: Set(name_pinyin),
+          name_initials: Set(name_initials),
           phone: Set(req.phone),
           email: Set(req.email),
           gender: Set(req.gender),
@@ -231,22 +290,68 @@ impl EmployeeRepository {
           ..Default::default()
       };
   
+      let txn = self.conn.begin().await.map_err(|e| {
+          tracing::error!(error = ?e, "error begin transaction");
+          ApiErr::ErrSystem(None)
+      })?;
       if let Err(e) = TEmployee::update(model)
-              .exec(&self.conn)
+              .exec(&txn)
               .await{
                   tracing::error!(error = ?e, "error update t_employee");
                   return Err(ApiErr::ErrSystem(None));
               }
+      // 不能禁用最后一名可用的管理员
+      if req.disabled_flag == 1 && holds_admin(&txn, req.employee_id).await? {
+          check_admin_remains(&txn).await?;
+      }
+      // 部门或职位有变动时记录履历
+      let detected = ChangeType::detect(prev, assignment);
+      if let Some(change_type) = detected {
+          let entry = Entry { employee_id: current.employee_id, change_type, prev, current: assignment };
+          history::record(&txn, vec![entry], &change).await?;
+      }
+      // 调整部门或被禁用后，其负责的部门需重新校验
+      if detected.is_some() || (req.disabled_flag == 1 && current.disabled_flag == 0) {
+          check_managed_by(&txn, &[current.employee_id]).await?;
+      }
+      txn.commit().await.map_err(|e| {
+          tracing::error!(error = ?e, "error commit transaction");
+          ApiErr::ErrSystem(None)
+      })?;
+      permission::invalidate_employee(req.employee_id);
               Ok(ApiOK(None))
   }
   
     // 禁用
     pub async fn disabled_flag(&self, employee_id: i64, disabled_flag:u8) -> Result<ApiOK<()>> {
-        let _update_model = TEmployee::update_many()
-            .col_expr(t_employee::Column::DeletedFlag, Expr::value(disabled_flag))
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        if let Err(e) = TEmployee::update_many()
+            .col_expr(t_employee::Column::DisabledFlag, Expr::value(disabled_flag))
             .filter(t_employee::Column::EmployeeId.eq(employee_id))
-            .exec(&self.conn)
-            .await;   
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        // 不能禁用最后一名可用的管理员
+        if disabled_flag == 1 && holds_admin(&txn, employee_id).await? {
+            check_admin_remains(&txn).await?;
+        }
+        // 禁用的员工不能担任部门负责人
+        if disabled_flag == 1 {
+            check_managed_by(&txn, &[employee_id]).await?;
+        }
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        permission::invalidate_employee(employee_id);
         
         Ok(ApiOK(None))
     }
@@ -256,23 +361,938 @@ impl EmployeeRepository {
             let _update_model = TEmployee::update_many()
                 .col_expr(t_employee::Column::LoginPwd, Expr::value(Crypto::md5("123456".as_bytes()).to_string()))
                 .filter(t_employee::Column::EmployeeId.eq(employee_id))
+                .filter(t_employee::Column::DeletedFlag.eq(0))
                 .exec(&self.conn)
                 .await;     
     
         Ok(ApiOK(None))
     }
     
-    // 调整部门
-    pub async fn change_department(&self, employee_id: Vec<i64>, department_id:i64) -> Result<ApiOK<()>> {
-            let _update_model = TEmployee::update_many()
-                    .col_expr(t_employee::Column::DepartmentId, Expr::value(department_id))
-                    .filter(t_employee::Column::EmployeeId.is_in(employee_id))
-                    .exec(&self.conn)
-                    .await;    
+    // 调整部门，并为实际调动的员工记录履历
+    pub async fn change_department(&self, employee_id: Vec<i64>, department_id:i64, change: Change) -> Result<ApiOK<()>> {
+            let txn = self.conn.begin().await.map_err(|e| {
+                tracing::error!(error = ?e, "error begin transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            let moved = history::move_department(&txn, &employee_id, department_id, &change).await?;
+            // 调离后不能再担任原部门的负责人
+            check_managed_by(&txn, &moved).await?;
+            txn.commit().await.map_err(|e| {
+                tracing::error!(error = ?e, "error commit transaction");
+                ApiErr::ErrSystem(None)
+            })?;
             Ok(ApiOK(None))
     }
 
 
+    // 删除员工（逻辑删除）：同时清空登录令牌，按配置 employee.delete_keep_roles 决定是否保留角色
+    pub async fn delete(&self, employee_id: i64, operator_id: i64) -> Result<ApiOK<()>> {
+        if employee_id == operator_id {
+            return Err(ApiErr::ErrPerm(Some("不能删除当前登录账号".to_string())));
+        }
+        let model = self.find_employee(employee_id, 0).await?;
+        let keep_roles = config::global().get_bool("employee.delete_keep_roles").unwrap_or_default();
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        let admin = holds_admin(&txn, employee_id).await?;
+
+        if let Err(e) = TEmployee::update_many()
+            .col_expr(t_employee::Column::DeletedFlag, Expr::value(1))
+            .col_expr(t_employee::Column::LoginToken, Expr::value(""))
+            .col_expr(t_employee::Column::UpdateTime, Expr::value(now))
+            .filter(t_employee::Column::EmployeeId.eq(employee_id))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        if !keep_roles {
+            if let Err(e) = TRoleEmployee::delete_many()
+                .filter(t_role_employee::Column::EmployeeId.eq(employee_id))
+                .exec(&txn)
+                .await
+            {
+                tracing::error!(error = ?e, "error delete t_role_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+        // 不论是否保留角色，已删除员工都不再计入管理员成员
+        if admin {
+            check_admin_remains(&txn).await?;
+        }
+        // 仍担任部门负责人时不能删除，需先调整部门负责人
+        check_managed_by(&txn, &[employee_id]).await?;
+        let assignment = (model.department_id, model.position_id);
+        let entry = Entry { employee_id, change_type: ChangeType::Leave, prev: assignment, current: assignment };
+        history::record(&txn, vec![entry], &Change::new(operator_id, None, Some("删除员工".to_string()))?).await?;
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        permission::invalidate_employee(employee_id);
+
+        Ok(ApiOK(None))
+    }
+
+    // 办理离职：在同一事务中禁用账号、清除登录会话、归档并移除角色、移交部门负责人，并保存离职记录
+    pub async fn offboard(&self, req: ReqOffboard, operator_id: i64) -> Result<ApiOK<RespOffboard>> {
+        if req.employee_id == operator_id {
+            return Err(ApiErr::ErrPerm(Some("不能为当前登录账号办理离职".to_string())));
+        }
+        let employee = self.find_employee(req.employee_id, 0).await?;
+        if employee.disabled_flag == 1 && self.latest_offboard(req.employee_id).await?.is_some() {
+            return Err(ApiErr::ErrPerm(Some("该员工已办理离职".to_string())));
+        }
+        let resign_date = xtime::to_timestamp(xtime::DATETIME, &format!("{} 00:00:00", req.resign_date), offset!(+8))
+            .map_err(|_| ApiErr::ErrParams(Some("离职日期格式错误".to_string())))?;
+
+        // 担任负责人的部门需要指定接任人
+        let departments = TDepartment::find()
+            .filter(t_department::Column::ManagerId.eq(req.employee_id))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        let successor = match req.successor_id.filter(|id| *id > 0) {
+            Some(successor_id) => {
+                if successor_id == req.employee_id {
+                    return Err(ApiErr::ErrParams(Some("接任人不能是离职员工本人".to_string())));
+                }
+                let successor = self
+                    .find_employee(successor_id, 0)
+                    .await
+                    .map_err(|_| ApiErr::ErrParams(Some("接任人不存在".to_string())))?;
+                if successor.disabled_flag == 1 {
+                    return Err(ApiErr::ErrParams(Some("接任人账号已禁用".to_string())));
+                }
+                Some(successor)
+            }
+            None if !departments.is_empty() => {
+                let names: Vec<&str> = departments.iter().map(|d| d.department_name.as_str()).collect();
+                return Err(ApiErr::ErrParams(Some(format!("该员工是部门负责人，请指定接任人：{}", names.join(",")))));
+            }
+            None => None,
+        };
+
+        let assignments = TRoleEmployee::find()
+            .filter(t_role_employee::Column::EmployeeId.eq(req.employee_id))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        let role_names: HashMap<i64, String> = TRole::find()
+            .select_only()
+            .column(t_role::Column::RoleId)
+            .column(t_role::Column::RoleName)
+            .filter(t_role::Column::RoleId.is_in(assignments.iter().map(|a| a.role_id)))
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect();
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let mut resp = RespOffboard {
+            offboard_id: 0,
+            employee_id: employee.employee_id,
+            realname: employee.realname,
+            resign_date: req.resign_date,
+            reason: req.reason,
+            disabled: employee.disabled_flag == 0,
+            sessions_revoked: !employee.login_token.is_empty(),
+            archived_roles: assignments
+                .iter()
+                .map(|a| RespOffboardRole {
+                    role_id: a.role_id,
+                    role_name: role_names.get(&a.role_id).cloned().unwrap_or_default(),
+                    valid_from: a.valid_from,
+                    valid_until: a.valid_until,
+                })
+                .collect(),
+            reassigned_departments: departments
+                .iter()
+                .map(|d| RespOffboardDepartment {
+                    department_id: d.department_id,
+                    department_name: d.department_name.clone(),
+                })
+                .collect(),
+            successor_id: successor.as_ref().map(|s| s.employee_id).unwrap_or_default(),
+            successor_name: successor.map(|s| s.realname).unwrap_or_default(),
+            operator_id,
+            create_time: now,
+            create_time_str: xtime::to_string(xtime::DATETIME, now, offset!(+8)).unwrap_or_default(),
+        };
+
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        let admin = holds_admin(&txn, req.employee_id).await?;
+
+        if let Err(e) = TEmployee::update_many()
+            .col_expr(t_employee::Column::DisabledFlag, Expr::value(1))
+            .col_expr(t_employee::Column::LoginToken, Expr::value(""))
+            .col_expr(t_employee::Column::UpdateTime, Expr::value(now))
+            .filter(t_employee::Column::EmployeeId.eq(req.employee_id))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        if let Err(e) = TRoleEmployee::delete_many()
+            .filter(t_role_employee::Column::EmployeeId.eq(req.employee_id))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error delete t_role_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        // 不能为最后一名可用的管理员办理离职
+        if admin {
+            check_admin_remains(&txn).await?;
+        }
+
+        if !departments.is_empty() {
+            if let Err(e) = TDepartment::update_many()
+                .col_expr(t_department::Column::ManagerId, Expr::value(resp.successor_id))
+                .col_expr(t_department::Column::UpdateTime, Expr::value(now))
+                .filter(t_department::Column::ManagerId.eq(req.employee_id))
+                .exec(&txn)
+                .await
+            {
+                tracing::error!(error = ?e, "error update t_department");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            // 接任人同样须是这些部门或其上级部门的员工
+            check_managers(&txn, departments.iter().map(|d| d.department_id).collect()).await?;
+        }
+        // 账号已禁用，不能仍是其他部门（如读取后新指派）的负责人
+        check_managed_by(&txn, &[req.employee_id]).await?;
+
+        let record = t_employee_offboard::ActiveModel {
+            employee_id: Set(req.employee_id),
+            resign_date: Set(resign_date),
+            reason: Set(resp.reason.clone()),
+            successor_id: Set(resp.successor_id),
+            summary: Set(serde_json::to_string(&resp).unwrap_or_default()),
+            operator_id: Set(operator_id),
+            create_time: Set(now),
+            ..Default::default()
+        };
+        let result = TEmployeeOffboard::insert(record).exec(&txn).await.map_err(|e| {
+            tracing::error!(error = ?e, "error insert t_employee_offboard");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        // 履历以离职日期生效
+        let assignment = (employee.department_id, employee.position_id);
+        let entry = Entry { employee_id: req.employee_id, change_type: ChangeType::Leave, prev: assignment, current: assignment };
+        let change = Change { effective_time: resign_date, operator_id, reason: resp.reason.clone() };
+        history::record(&txn, vec![entry], &change).await?;
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        permission::invalidate_employee(req.employee_id);
+
+        resp.offboard_id = result.last_insert_id;
+        Ok(ApiOK(Some(resp)))
+    }
+
+    // 员工最近一次的离职记录
+    pub async fn offboard_info(&self, employee_id: i64) -> Result<ApiOK<RespOffboard>> {
+        let model = self
+            .latest_offboard(employee_id)
+            .await?
+            .ok_or(ApiErr::ErrNotFound(Some("离职记录不存在".to_string())))?;
+        let mut resp: RespOffboard = serde_json::from_str(&model.summary).map_err(|e| {
+            tracing::error!(error = ?e, "error parse t_employee_offboard summary");
+            ApiErr::ErrSystem(None)
+        })?;
+        resp.offboard_id = model.offboard_id;
+        Ok(ApiOK(Some(resp)))
+    }
+
+    async fn latest_offboard(&self, employee_id: i64) -> Result<Option<t_employee_offboard::Model>> {
+        TEmployeeOffboard::find()
+            .filter(t_employee_offboard::Column::EmployeeId.eq(employee_id))
+            .order_by(t_employee_offboard::Column::OffboardId, Order::Desc)
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee_offboard");
+                ApiErr::ErrSystem(None)
+            })
+    }
+
+    // 恢复已删除员工，原部门已删除时需指定新部门，原职务已删除时恢复为未分配职务
+    pub async fn restore(&self, employee_id: i64, department_id: Option<i64>, operator_id: i64) -> Result<ApiOK<()>> {
+        let model = self.find_employee(employee_id, 1).await?;
+        let department_id = department_id.unwrap_or(model.department_id);
+
+        let department_count = TDepartment::find()
+            .filter(t_department::Column::DepartmentId.eq(department_id))
+            .count(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error count t_department");
+                ApiErr::ErrSystem(None)
+            })?;
+        if department_count == 0 {
+            return Err(ApiErr::ErrParams(Some("部门不存在，请重新指定部门".to_string())));
+        }
+        // 删除期间原职务也被删除时，恢复为未分配职务
+        let position_id = match self.check_position(model.position_id).await {
+            Ok(()) => model.position_id,
+            Err(ApiErr::ErrParams(_)) => 0,
+            Err(e) => return Err(e),
+        };
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        if let Err(e) = TEmployee::update_many()
+            .col_expr(t_employee::Column::DeletedFlag, Expr::value(0))
+            .col_expr(t_employee::Column::DepartmentId, Expr::value(department_id))
+            .col_expr(t_employee::Column::PositionId, Expr::value(position_id))
+            .col_expr(t_employee::Column::UpdateTime, Expr::value(now))
+            .filter(t_employee::Column::EmployeeId.eq(employee_id))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        let entry = Entry {
+            employee_id,
+            change_type: ChangeType::Restore,
+            prev: (model.department_id, model.position_id),
+            current: (department_id, position_id),
+        };
+        history::record(&txn, vec![entry], &Change::new(operator_id, None, None)?).await?;
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        permission::invalidate_employee(employee_id);
+
+        Ok(ApiOK(None))
+    }
+
+    // 批量导入员工：部门与职位按名称匹配，登录名与手机号的唯一性校验同新增员工
+    pub async fn import(&self, rows: Vec<ImportRow>, dry_run: bool, strict: bool, operator_id: i64) -> Result<RespImportReport> {
+        let departments = self.name_index(
+            TDepartment::find()
+                .select_only()
+                .column(t_department::Column::DepartmentName)
+                .column(t_department::Column::DepartmentId)
+                .into_tuple::<(String, i64)>()
+                .all(&self.conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error find t_department");
+                    ApiErr::ErrSystem(None)
+                })?,
+        );
+        let positions = self.name_index(
+            TPosition::find()
+                .select_only()
+                .column(t_position::Column::PositionName)
+                .column(t_position::Column::PositionId)
+                .filter(t_position::Column::DeletedFlag.eq(0))
+                .into_tuple::<(String, i64)>()
+                .all(&self.conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error find t_position");
+                    ApiErr::ErrSystem(None)
+                })?,
+        );
+
+        // 与新增员工一致，已删除员工的登录名与手机号同样视为占用
+        let existing = TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::LoginName)
+            .column(t_employee::Column::Phone)
+            .filter(
+                Condition::any()
+                    .add(t_employee::Column::LoginName.is_in(rows.iter().map(|row| row.login_name.clone())))
+                    .add(t_employee::Column::Phone.is_in(rows.iter().map(|row| row.phone.clone()))),
+            )
+            .into_tuple::<(String, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        let mut login_names: HashSet<String> = existing.iter().map(|(login_name, _)| login_name.clone()).collect();
+        let mut phones: HashSet<String> = existing.into_iter().map(|(_, phone)| phone).collect();
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let total = rows.len();
+        let mut models = Vec::with_capacity(total);
+        let mut failed = Vec::new();
+        for row in rows {
+            let mut errors = Vec::new();
+            for (value, label) in [
+                (&row.realname, "员工姓名"),
+                (&row.phone, "手机号码"),
+                (&row.login_name, "登录名"),
+                (&row.email, "邮箱"),
+            ] {
+                if value.is_empty() {
+                    errors.push(format!("{}必填", label));
+                }
+            }
+            if !row.phone.is_empty() && validate_phone(&row.phone).is_err() {
+                errors.push("手机号码格式错误".to_string());
+            }
+            if !row.email.is_empty() && !row.email.validate_email() {
+                errors.push("邮箱格式错误".to_string());
+            }
+            if login_names.contains(&row.login_name) {
+                errors.push("登录名已重复".to_string());
+            }
+            if phones.contains(&row.phone) {
+                errors.push("手机号码已重复".to_string());
+            }
+
+            let gender = match row.gender.as_str() {
+                "" | "0" | "未知" => Some(0),
+                "1" | "男" => Some(1),
+                "2" | "女" => Some(2),
+                _ => None,
+            };
+            if gender.is_none() {
+                errors.push(format!("性别无法识别：{}", row.gender));
+            }
+            let disabled_flag = match row.disabled.as_str() {
+                "" | "0" | "否" => Some(0),
+                "1" | "是" => Some(1),
+                _ => None,
+            };
+            if disabled_flag.is_none() {
+                errors.push(format!("禁用状态无法识别：{}", row.disabled));
+            }
+
+            let department_id = match departments.get(&row.department) {
+                _ if row.department.is_empty() => {
+                    errors.push("部门必填".to_string());
+                    None
+                }
+                Some(Some(id)) => Some(*id),
+                Some(None) => {
+                    errors.push(format!("部门名称不唯一：{}", row.department));
+                    None
+                }
+                None => {
+                    errors.push(format!("部门不存在：{}", row.department));
+                    None
+                }
+            };
+            let position_id = match positions.get(&row.position) {
+                _ if row.position.is_empty() => Some(0),
+                Some(Some(id)) => Some(*id),
+                Some(None) => {
+                    errors.push(format!("职位名称不唯一：{}", row.position));
+                    None
+                }
+                None => {
+                    errors.push(format!("职位不存在：{}", row.position));
+                    None
+                }
+            };
+
+            if !errors.is_empty() {
+                failed.push(RespImportError { data: row, errors });
+                continue;
+            }
+            // 校验全部通过的行才占用登录名与手机号，失败行修正后在同一文件中重复出现不算重复
+            login_names.insert(row.login_name.clone());
+            phones.insert(row.phone.clone());
+            let (name_pinyin, name_initials) = search::keys(&row.realname);
+            models.push(t_employee::ActiveModel {
+                realname: Set(row.realname),
+                name_pinyin: Set(name_pinyin),
+                name_initials: Set(name_initials),
+                phone: Set(row.phone),
+                department_id: Set(department_id.unwrap_or_default()),
+                login_pwd: Set(Crypto::md5(row.login_name.as_bytes()).to_string()),
+                login_name: Set(row.login_name),
+                email: Set(row.email),
+                gender: Set(gender.unwrap_or_default()),
+                disabled_flag: Set(disabled_flag.unwrap_or_default()),
+                position_id: Set(position_id.unwrap_or_default()),
+                create_time: Set(now),
+                update_time: Set(now),
+                ..Default::default()
+            });
+        }
+
+        let valid = models.len();
+        let mut created = 0;
+        if !dry_run && !models.is_empty() && (!strict || failed.is_empty()) {
+            let txn = self.conn.begin().await.map_err(|e| {
+                tracing::error!(error = ?e, "error begin transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            let login_names: Vec<String> = models.iter().map(|model| model.login_name.as_ref().clone()).collect();
+            if let Err(e) = TEmployee::insert_many(models).exec(&txn).await {
+                tracing::error!(error = ?e, "error insert t_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            // 批量插入拿不到全部ID，按登录名查回后记录入职履历
+            let inserted = TEmployee::find()
+                .select_only()
+                .column(t_employee::Column::EmployeeId)
+                .column(t_employee::Column::DepartmentId)
+                .column(t_employee::Column::PositionId)
+                .filter(t_employee::Column::LoginName.is_in(login_names))
+                .into_tuple::<(i64, i64, i64)>()
+                .all(&txn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error find t_employee");
+                    ApiErr::ErrSystem(None)
+                })?;
+            let entries = inserted
+                .into_iter()
+                .map(|(employee_id, department_id, position_id)| Entry {
+                    employee_id,
+                    change_type: ChangeType::Hire,
+                    prev: (0, 0),
+                    current: (department_id, position_id),
+                })
+                .collect();
+            history::record(&txn, entries, &Change::new(operator_id, None, Some("批量导入".to_string()))?).await?;
+            txn.commit().await.map_err(|e| {
+                tracing::error!(error = ?e, "error commit transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            created = valid;
+        }
+
+        Ok(RespImportReport { dry_run, strict, total, valid, created, failed })
+    }
+
+    // 名称 -> ID，名称重复时为 None
+    fn name_index(&self, pairs: Vec<(String, i64)>) -> HashMap<String, Option<i64>> {
+        let mut index: HashMap<String, Option<i64>> = HashMap::new();
+        for (name, id) in pairs {
+            index
+                .entry(name)
+                .and_modify(|existing| *existing = None)
+                .or_insert(Some(id));
+        }
+        index
+    }
+
+    // 按列表查询条件导出一批员工，按员工ID升序，从 after_id 之后开始
+    pub async fn export_batch(&self, query: &HashMap<String, String>, after_id: i64, limit: u64) -> Result<Vec<RespExportRow>> {
+        let builder = self.filter_query(query, 0).await?
+            .filter(t_employee::Column::EmployeeId.gt(after_id))
+            .order_by(t_employee::Column::EmployeeId, Order::Asc)
+            .limit(limit);
+        let rows = Self::with_names(builder)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RespExportRow {
+                employee_id: row.employee_id,
+                realname: row.realname,
+                phone: row.phone,
+                login_name: row.login_name,
+                email: row.email,
+                gender: row.gender,
+                department_name: row.department_name.unwrap_or_default(),
+                position_name: row.position_name.unwrap_or_default(),
+                disabled_flag: row.disabled_flag,
+                create_time_str: xtime::to_string(xtime::DATETIME, row.create_time, offset!(+8))
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    // 按姓名（含拼音）、登录名、手机号码、邮箱搜索员工
+    pub async fn search(&self, query: HashMap<String, String>) -> Result<ApiOK<Vec<RespSearch>>> {
+        let keyword = search::normalize(query.get("keyword").map(String::as_str).unwrap_or_default());
+        if keyword.is_empty() {
+            return Ok(ApiOK(Some(Vec::new())));
+        }
+        let pinyin_keyword = search::pinyin_keyword(&keyword);
+
+        // 候选数量有限，先取完全匹配、再取前缀匹配的记录，避免被包含匹配挤出
+        let text_columns = [
+            t_employee::Column::Realname,
+            t_employee::Column::LoginName,
+            t_employee::Column::Phone,
+            t_employee::Column::Email,
+        ];
+        let pinyin_columns = [t_employee::Column::NamePinyin, t_employee::Column::NameInitials];
+        let (mut contains, mut exact, mut prefix) = (Condition::any(), Condition::any(), Condition::any());
+        for column in text_columns {
+            contains = contains.add(Expr::col((TEmployee, column)).like(search::like_contains(&keyword)));
+            exact = exact.add(column.eq(&keyword));
+            prefix = prefix.add(Expr::col((TEmployee, column)).like(search::like_prefix(&keyword)));
+        }
+        for column in pinyin_columns {
+            contains = contains.add(Expr::col((TEmployee, column)).like(search::like_contains(&pinyin_keyword)));
+            exact = exact.add(column.eq(&pinyin_keyword));
+            prefix = prefix.add(Expr::col((TEmployee, column)).like(search::like_prefix(&pinyin_keyword)));
+        }
+        let builder = TEmployee::find()
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .filter(contains)
+            .order_by(SimpleExpr::Case(Box::new(Expr::case(exact, 0).case(prefix, 1).finally(2))), Order::Asc)
+            .order_by(t_employee::Column::EmployeeId, Order::Asc)
+            .limit(search::CANDIDATES);
+        let rows = Self::with_names(builder)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let mut list: Vec<RespSearch> = rows
+            .into_iter()
+            .filter_map(|row| {
+                // 多个字段命中时取分值最高的
+                let (field, matched) = [
+                    ("realname", search::matches(&row.realname, &keyword, true)),
+                    ("login_name", search::matches(&row.login_name, &keyword, false)),
+                    ("phone", search::matches(&row.phone, &keyword, false)),
+                    ("email", search::matches(&row.email, &keyword, false)),
+                ]
+                .into_iter()
+                .filter_map(|(field, matched)| matched.map(|m| (field, m)))
+                .max_by(|a, b| a.1.score.cmp(&b.1.score).then(b.0.cmp(a.0)))?;
+                Some(RespSearch {
+                    employee_id: row.employee_id,
+                    realname: row.realname,
+                    login_name: row.login_name,
+                    phone: row.phone,
+                    email: row.email,
+                    department_id: row.department_id,
+                    department_name: row.department_name.unwrap_or_default(),
+                    field: field.to_string(),
+                    kind: matched.kind,
+                    score: matched.score,
+                    highlight: matched.highlight,
+                })
+            })
+            .collect();
+        list.sort_by(|a, b| b.score.cmp(&a.score).then(a.employee_id.cmp(&b.employee_id)));
+        list.truncate(search::limit(&query));
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 重新计算所有员工的姓名拼音，返回更新的数量
+    pub async fn rebuild_pinyin(&self) -> Result<u64> {
+        let mut updated = 0;
+        let mut after_id = 0;
+        loop {
+            let rows = TEmployee::find()
+                .select_only()
+                .column(t_employee::Column::EmployeeId)
+                .column(t_employee::Column::Realname)
+                .column(t_employee::Column::NamePinyin)
+                .column(t_employee::Column::NameInitials)
+                .filter(t_employee::Column::EmployeeId.gt(after_id))
+                .order_by(t_employee::Column::EmployeeId, Order::Asc)
+                .limit(500)
+                .into_tuple::<(i64, String, String, String)>()
+                .all(&self.conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error find t_employee");
+                    ApiErr::ErrSystem(None)
+                })?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after_id = last.0;
+
+            for (employee_id, realname, name_pinyin, name_initials) in rows {
+                let keys = search::keys(&realname);
+                if keys == (name_pinyin, name_initials) {
+                    continue;
+                }
+                let model = t_employee::ActiveModel {
+                    employee_id: Set(employee_id),
+                    name_pinyin: Set(keys.0),
+                    name_initials: Set(keys.1),
+                    ..Default::default()
+                };
+                if let Err(e) = TEmployee::update(model).exec(&self.conn).await {
+                    tracing::error!(error = ?e, "error update t_employee");
+                    return Err(ApiErr::ErrSystem(None));
+                }
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    // 左连接部门与职位表，一次查出名称
+    fn with_names(builder: Select<TEmployee>) -> Selector<SelectModel<EmployeeRow>> {
+        builder
+            .join(
+                JoinType::LeftJoin,
+                TEmployee::belongs_to(TDepartment)
+                    .from(t_employee::Column::DepartmentId)
+                    .to(t_department::Column::DepartmentId)
+                    .into(),
+            )
+            .join(
+                JoinType::LeftJoin,
+                TEmployee::belongs_to(TPosition)
+                    .from(t_employee::Column::PositionId)
+                    .to(t_position::Column::PositionId)
+                    .into(),
+            )
+            .column_as(t_department::Column::DepartmentName, "department_name")
+            .column_as(t_position::Column::PositionName, "position_name")
+            .into_model::<EmployeeRow>()
+    }
+
+    // 批量查询员工当前生效的角色名称
+    async fn role_names(&self, employee_ids: Vec<i64>) -> Result<HashMap<i64, Vec<String>>> {
+        let mut result: HashMap<i64, Vec<String>> = HashMap::new();
+        if employee_ids.is_empty() {
+            return Ok(result);
+        }
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let rows = TRoleEmployee::find()
+            .select_only()
+            .column(t_role_employee::Column::EmployeeId)
+            .column(t_role::Column::RoleName)
+            .join(
+                JoinType::InnerJoin,
+                TRoleEmployee::belongs_to(TRole)
+                    .from(t_role_employee::Column::RoleId)
+                    .to(t_role::Column::RoleId)
+                    .into(),
+            )
+            .filter(t_role_employee::Column::EmployeeId.is_in(employee_ids))
+            .filter(active_role_employee(now))
+            .order_by(t_role::Column::RoleId, Order::Asc)
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        for (employee_id, role_name) in rows {
+            result.entry(employee_id).or_default().push(role_name);
+        }
+        Ok(result)
+    }
+
+    // 列表与导出共用的查询条件
+    async fn filter_query(&self, query: &HashMap<String, String>, deleted_flag: u8) -> Result<Select<TEmployee>> {
+        let mut builder = TEmployee::find()
+            .filter(t_employee::Column::DeletedFlag.eq(deleted_flag));
+        if let Some(disabled_flag) = query.get("disabled_flag") {
+            if disabled_flag == "1" {
+                builder = builder.filter(t_employee::Column::DisabledFlag.eq(1));
+            } else {
+                builder = builder.filter(t_employee::Column::DisabledFlag.eq(0));
+            }
+        }
+
+        if let Some(login_name) = query.get("login_name") {
+            if !login_name.is_empty() {
+                builder = builder.filter(t_employee::Column::LoginName.contains(login_name));
+            }
+        }
+
+        if let Some(phone) = query.get("phone") {
+            if !phone.is_empty() {
+                builder = builder.filter(t_employee::Column::Phone.contains(phone));  
+            }
+        }
+
+        // 默认包含所有下级部门，include_sub=0 时只查该部门
+        if let Some(department_id) = query.get("department_id").and_then(|v| v.parse::<i64>().ok()) {
+            if query.get("include_sub").is_some_and(|v| v == "0") {
+                builder = builder.filter(t_employee::Column::DepartmentId.eq(department_id));
+            } else {
+                let department_ids = self.department.subtree_ids(department_id).await?;
+                builder = builder.filter(t_employee::Column::DepartmentId.is_in(department_ids));
+            }
+        }
+        Ok(builder)
+    }
+
+    // 个人资料
+    pub async fn profile(&self, employee_id: i64) -> Result<ApiOK<RespProfile>> {
+        let model = self.find_employee(employee_id, 0).await?;
+
+        let department_name = TDepartment::find_by_id(model.department_id)
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_department");
+                ApiErr::ErrSystem(None)
+            })?
+            .map(|department| department.department_name)
+            .unwrap_or_default();
+        let position_name = TPosition::find_by_id(model.position_id)
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_position");
+                ApiErr::ErrSystem(None)
+            })?
+            .map(|position| position.position_name)
+            .unwrap_or_default();
+
+        let resp = RespProfile {
+            employee_id: model.employee_id,
+            login_name: model.login_name,
+            realname: model.realname,
+            avatar: model.avatar,
+            gender: model.gender,
+            phone: model.phone,
+            email: model.email,
+            department_id: model.department_id,
+            department_name,
+            position_id: model.position_id,
+            position_name,
+            administrator_flag: model.administrator_flag,
+            remark: model.remark,
+            create_time: model.create_time,
+            create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
+                .unwrap_or_default(),
+        };
+        Ok(ApiOK(Some(resp)))
+    }
+
+    // 修改个人资料，只更新传入的字段
+    pub async fn update_profile(&self, employee_id: i64, req: ReqProfile) -> Result<ApiOK<()>> {
+        self.find_employee(employee_id, 0).await?;
+
+        if let Some(phone) = &req.phone {
+            let count = TEmployee::find()
+                .filter(t_employee::Column::Phone.eq(phone.clone()))
+                .filter(t_employee::Column::EmployeeId.ne(employee_id))
+                .count(&self.conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error count phone");
+                    ApiErr::ErrSystem(None)
+                })?;
+            if count > 0 {
+                return Err(ApiErr::ErrPerm(Some("手机号码已重复".to_string())));
+            }
+        }
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let mut model = t_employee::ActiveModel {
+            employee_id: Set(employee_id),
+            update_time: Set(now),
+            ..Default::default()
+        };
+        if let Some(avatar) = req.avatar {
+            model.avatar = Set(avatar);
+        }
+        if let Some(email) = req.email {
+            model.email = Set(email);
+        }
+        if let Some(phone) = req.phone {
+            model.phone = Set(phone);
+        }
+        if let Some(remark) = req.remark {
+            model.remark = Set(remark);
+        }
+
+        if let Err(e) = TEmployee::update(model).exec(&self.conn).await {
+            tracing::error!(error = ?e, "error update t_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        Ok(ApiOK(None))
+    }
+
+    // 更新头像地址
+    pub async fn update_avatar(&self, employee_id: i64, avatar: String) -> Result<()> {
+        self.find_employee(employee_id, 0).await?;
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        if let Err(e) = TEmployee::update_many()
+            .col_expr(t_employee::Column::Avatar, Expr::value(avatar))
+            .col_expr(t_employee::Column::UpdateTime, Expr::value(now))
+            .filter(t_employee::Column::EmployeeId.eq(employee_id))
+            .exec(&self.conn)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        Ok(())
+    }
+
+    async fn find_employee(&self, employee_id: i64, deleted_flag: u8) -> Result<t_employee::Model> {
+        TEmployee::find_by_id(employee_id)
+            .filter(t_employee::Column::DeletedFlag.eq(deleted_flag))
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("员工信息不存在".to_string())))
+    }
+
+    // 已删除的职务不能再分配给员工，0 表示未分配职务
+    async fn check_position(&self, position_id: i64) -> Result<()> {
+        if position_id == 0 {
+            return Ok(());
+        }
+        let count = TPosition::find()
+            .filter(t_position::Column::PositionId.eq(position_id))
+            .filter(t_position::Column::DeletedFlag.eq(0))
+            .count(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error count t_position");
+                ApiErr::ErrSystem(None)
+            })?;
+        if count == 0 {
+            return Err(ApiErr::ErrParams(Some("职务不存在或已删除".to_string())));
+        }
+        Ok(())
+    }
+
     //人员下拉框
     pub async fn employee_select_list(&self) -> Result<ApiOK<Vec<RespSelectOption>>> {
         
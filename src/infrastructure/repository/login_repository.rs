@@ -1,6 +1,6 @@
 use crate::common::utils;
 use crate::infrastructure::persistence::database as db;
-use crate::application::dto::login_dto::{ReqLogin, RespLogin};
+use crate::application::dto::login_dto::{ReqLogin, RespLogin, RespCurrentUser, RespUserRole};
 use crate::common::{
     result::response::{ApiErr, ApiOK, Result},
     xtime
@@ -10,25 +10,27 @@ use sea_orm::{
 };
 
 use crate::domain::entities::{
-    t_employee, prelude::TEmployee,
-    t_role_employee, prelude::TRoleEmployee
+    t_employee, prelude::TEmployee
 };
 use sea_orm::sea_query::Expr;
 
 use crate::common::crypto::hash::Crypto;
 use time::macros::offset;
 use crate::infrastructure::security::identity::Identity;
+use crate::infrastructure::repository::permission_repository::PermissionRepository;
 
 
 pub struct LoginRepository {
-    conn: DatabaseConnection
+    conn: DatabaseConnection,
+    permission: PermissionRepository,
 }
 
 impl LoginRepository {
 
     pub fn new() -> Self {
         Self {
-            conn: db::conn().clone()
+            conn: db::conn().clone(),
+            permission: PermissionRepository::new(),
         }
     }
 
@@ -40,6 +42,7 @@ impl LoginRepository {
         /* 根据用户名查询sys_user表，返回用户对象 */
         let  model = TEmployee::find()
             .filter(t_employee::Column::LoginName.eq(req.username))
+            .filter(t_employee::Column::DeletedFlag.eq(0))
             .one(&self.conn)
             .await
             .map_err(|e| {
@@ -48,16 +51,8 @@ impl LoginRepository {
             })?
             .ok_or(ApiErr::ErrAuth(Some("账号不存在".to_string())))?;
 
-            /* 根据用户ID查询 sys_user_role表，返回用户角色关系表对象 */
-            let t_role_employee  = TRoleEmployee::find()
-                .filter(t_role_employee::Column::EmployeeId.eq(model.employee_id))
-                .one(&self.conn)
-                .await
-                .map_err(|e| {
-                    tracing::error!(error = ?e, "error find sys_user_role");
-                    ApiErr::ErrSystem(None)
-                })?
-                .ok_or(ApiErr::ErrAuth(Some("账号角色关系不存在".to_string())))?;
+            /* 查询员工的全部角色，未分配角色的员工同样允许登录，权限为空 */
+            let roles = self.permission.employee_roles(model.employee_id).await?;
 
             let pass = format!("{}", req.password);
             // Crypto::md5(data);
@@ -65,6 +60,11 @@ impl LoginRepository {
                 return Err(ApiErr::ErrAuth(Some("密码错误".to_string())));
             }
 
+            // 禁用（含已离职）的账号不允许登录
+            if model.disabled_flag == 1 {
+                return Err(ApiErr::ErrAuth(Some("账号已禁用".to_string())));
+            }
+
             let now = xtime::now(offset!(+8)).unix_timestamp();
             //自定义token
             let login_token = Crypto::md5(format!("auth.{}.{}.{}", model.employee_id, now, utils::nonce(16)).as_bytes());
@@ -96,7 +96,7 @@ impl LoginRepository {
         
             let resp = RespLogin {
                 name: model.realname,
-                role: t_role_employee.role_id,
+                roles: roles.into_iter().map(|role| role.role_id).collect(),
                 auth_token,
             };
         
@@ -122,4 +122,45 @@ impl LoginRepository {
 
         Ok(ApiOK(None))
     }
-}
\ No newline at end of file
+
+    /** 当前登录员工信息：基本资料、角色、菜单路由及按钮权限 */
+    pub async fn current_user(&self, identity: Identity) -> Result<ApiOK<RespCurrentUser>> {
+        let model = TEmployee::find_by_id(identity.id())
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrAuth(Some("账号不存在".to_string())))?;
+
+        let roles = self.permission.employee_roles(model.employee_id).await?;
+        let menu_ids = self.permission.employee_menu_ids(&model).await?;
+        let (menus, web_perms) = self.permission.menu_routes(menu_ids).await?;
+
+        let resp = RespCurrentUser {
+            employee_id: model.employee_id,
+            login_name: model.login_name,
+            realname: model.realname,
+            avatar: model.avatar,
+            phone: model.phone,
+            email: model.email,
+            department_id: model.department_id,
+            position_id: model.position_id,
+            administrator_flag: model.administrator_flag,
+            roles: roles
+                .into_iter()
+                .map(|role| RespUserRole {
+                    role_id: role.role_id,
+                    role_name: role.role_name,
+                    role_code: role.role_code,
+                })
+                .collect(),
+            menus,
+            web_perms,
+        };
+
+        Ok(ApiOK(Some(resp)))
+    }
+}
@@ -0,0 +1,449 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::infrastructure::persistence::database as db;
+use crate::infrastructure::security::permission;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, QueryOrder,
+    Set, TransactionTrait,
+};
+use crate::domain::entities::{
+    t_menu, prelude::TMenu,
+    t_role, prelude::TRole,
+    t_role_menu, prelude::TRoleMenu
+};
+use crate::application::dto::menu_dto::{
+    MenuDocument, MenuEntry, RoleMenuEntry, RespImportReport, MenuChange, RoleMenuChange,
+    MENU_DOCUMENT_VERSION,
+};
+use crate::common::{
+    result::response::{ApiErr, ApiOK, Result},
+    xtime,
+};
+use time::macros::offset;
+
+// key 中上下级菜单名称的分隔符
+const KEY_SEPARATOR: &str = "/";
+
+pub struct MenuRepository {
+    conn: DatabaseConnection
+}
+
+impl MenuRepository {
+    pub fn new() -> Self {
+        Self {
+            conn: db::conn().clone()
+        }
+    }
+
+    // 导出全部菜单及角色功能绑定
+    pub async fn export(&self) -> Result<ApiOK<MenuDocument>> {
+        let models = self.menus().await?;
+        let keys = menu_keys(&models);
+
+        let mut menus: Vec<MenuEntry> = models.iter().map(|model| to_entry(model, &keys)).collect();
+        // 上级菜单排在前面，便于导入时按顺序创建
+        menus.sort_by_key(|entry| {
+            (entry.key.matches(KEY_SEPARATOR).count(), entry.sort.unwrap_or_default(), entry.key.clone())
+        });
+
+        let roles = TRole::find()
+            .order_by_asc(t_role::Column::RoleCode)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?;
+        let grants = self.role_menu_ids().await?;
+
+        let role_menus = roles
+            .into_iter()
+            .map(|role| {
+                let menu_keys: BTreeSet<String> = grants
+                    .get(&role.role_id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|menu_id| keys.get(menu_id).cloned())
+                    .collect();
+                RoleMenuEntry {
+                    role_code: role.role_code,
+                    menu_keys: menu_keys.into_iter().collect(),
+                }
+            })
+            .collect();
+
+        Ok(ApiOK(Some(MenuDocument {
+            version: MENU_DOCUMENT_VERSION,
+            menus,
+            role_menus,
+        })))
+    }
+
+    // 导入菜单：按 key 新增或更新，角色功能绑定整体替换；dry_run 时只返回差异
+    pub async fn import(&self, doc: MenuDocument, operator_id: i64, dry_run: bool) -> Result<ApiOK<RespImportReport>> {
+        if doc.version != MENU_DOCUMENT_VERSION {
+            return Err(ApiErr::ErrParams(Some(format!("不支持的文档版本：{}", doc.version))));
+        }
+
+        let models = self.menus().await?;
+        let keys = menu_keys(&models);
+        let existing: HashMap<&str, &t_menu::Model> = models
+            .iter()
+            .map(|model| (keys[&model.menu_id].as_str(), model))
+            .collect();
+
+        let entries = sort_entries(&doc.menus, &existing)?;
+        let mut known: HashSet<&str> = existing.keys().copied().collect();
+        known.extend(entries.iter().map(|entry| entry.key.as_str()));
+        if let Some(key) = entries
+            .iter()
+            .filter_map(|entry| entry.context_menu_key.as_ref())
+            .find(|key| !known.contains(key.as_str()))
+        {
+            return Err(ApiErr::ErrParams(Some(format!("关联菜单不存在：{}", key))));
+        }
+        for binding in &doc.role_menus {
+            if let Some(key) = binding.menu_keys.iter().find(|key| !known.contains(key.as_str())) {
+                return Err(ApiErr::ErrParams(Some(format!("角色 {} 绑定的菜单不存在：{}", binding.role_code, key))));
+            }
+        }
+
+        let mut report = RespImportReport {
+            dry_run,
+            ..Default::default()
+        };
+
+        let txn = match dry_run {
+            true => None,
+            false => Some(self.conn.begin().await.map_err(|e| {
+                tracing::error!(error = ?e, "error begin transaction");
+                ApiErr::ErrSystem(None)
+            })?),
+        };
+
+        // 第一步：按上下级顺序新增或更新菜单
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let mut key_ids: HashMap<String, i64> = keys.iter().map(|(id, key)| (key.clone(), *id)).collect();
+        let mut pending_context: Vec<&MenuEntry> = Vec::new();
+        for entry in entries {
+            let parent_id = entry
+                .parent_key
+                .as_ref()
+                .and_then(|key| key_ids.get(key).copied())
+                .unwrap_or_default();
+            let context_menu_id = entry.context_menu_key.as_ref().and_then(|key| key_ids.get(key).copied());
+            if entry.context_menu_key.is_some() && context_menu_id.is_none() {
+                pending_context.push(entry);
+            }
+
+            match existing.get(entry.key.as_str()) {
+                Some(model) => {
+                    let fields = diff(&to_entry(model, &keys), entry);
+                    if fields.is_empty() {
+                        report.unchanged += 1;
+                        continue;
+                    }
+                    if let Some(txn) = &txn {
+                        let mut active = to_active(entry, parent_id, context_menu_id);
+                        active.menu_id = Set(model.menu_id);
+                        active.update_user_id = Set(Some(operator_id));
+                        active.update_time = Set(now);
+                        if let Err(e) = TMenu::update(active).exec(txn).await {
+                            tracing::error!(error = ?e, "error update t_menu");
+                            return Err(ApiErr::ErrSystem(None));
+                        }
+                    }
+                    report.updated.push(MenuChange { key: entry.key.clone(), fields });
+                }
+                None => {
+                    if let Some(txn) = &txn {
+                        let mut active = to_active(entry, parent_id, context_menu_id);
+                        active.deleted_flag = Set(0);
+                        active.create_user_id = Set(operator_id);
+                        active.create_time = Set(now);
+                        active.update_time = Set(now);
+                        let menu_id = TMenu::insert(active)
+                            .exec(txn)
+                            .await
+                            .map_err(|e| {
+                                tracing::error!(error = ?e, "error insert t_menu");
+                                ApiErr::ErrSystem(None)
+                            })?
+                            .last_insert_id;
+                        key_ids.insert(entry.key.clone(), menu_id);
+                    }
+                    report.created.push(entry.key.clone());
+                }
+            }
+        }
+
+        // 第二步：补全引用了后创建菜单的关联菜单
+        if let Some(txn) = &txn {
+            for entry in pending_context {
+                let menu_id = key_ids[&entry.key];
+                let context_menu_id = entry.context_menu_key.as_ref().and_then(|key| key_ids.get(key).copied());
+                let active = t_menu::ActiveModel {
+                    menu_id: Set(menu_id),
+                    context_menu_id: Set(context_menu_id),
+                    ..Default::default()
+                };
+                if let Err(e) = TMenu::update(active).exec(txn).await {
+                    tracing::error!(error = ?e, "error update t_menu");
+                    return Err(ApiErr::ErrSystem(None));
+                }
+            }
+        }
+
+        // 第三步：按角色编码替换角色功能绑定
+        let roles: HashMap<String, i64> = TRole::find()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .map(|role| (role.role_code, role.role_id))
+            .collect();
+        let grants = self.role_menu_ids().await?;
+        let mut changed_roles = Vec::new();
+        for binding in &doc.role_menus {
+            let Some(role_id) = roles.get(&binding.role_code).copied() else {
+                report.missing_roles.push(binding.role_code.clone());
+                continue;
+            };
+
+            let current: BTreeSet<&str> = grants
+                .get(&role_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|menu_id| keys.get(menu_id).map(|key| key.as_str()))
+                .collect();
+            let desired: BTreeSet<&str> = binding.menu_keys.iter().map(|key| key.as_str()).collect();
+            if current == desired {
+                continue;
+            }
+
+            if let Some(txn) = &txn {
+                let menu_ids = desired.iter().map(|key| key_ids[*key]).collect();
+                replace_role_menu(txn, role_id, menu_ids, now).await?;
+                changed_roles.push(role_id);
+            }
+            report.role_menus.push(RoleMenuChange {
+                role_code: binding.role_code.clone(),
+                added: desired.difference(&current).map(|key| key.to_string()).collect(),
+                removed: current.difference(&desired).map(|key| key.to_string()).collect(),
+            });
+        }
+
+        if let Some(txn) = txn {
+            txn.commit().await.map_err(|e| {
+                tracing::error!(error = ?e, "error commit transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            // 菜单本身有变更时，禁用或删除状态会影响所有员工（含超级管理员）的有效权限
+            if report.created.is_empty() && report.updated.is_empty() {
+                for role_id in changed_roles {
+                    permission::invalidate_role(role_id);
+                }
+            } else {
+                permission::invalidate_all();
+            }
+        }
+
+        Ok(ApiOK(Some(report)))
+    }
+
+    // 查询所有未删除的菜单
+    async fn menus(&self) -> Result<Vec<t_menu::Model>> {
+        TMenu::find()
+            .filter(t_menu::Column::DeletedFlag.eq(0))
+            .order_by_asc(t_menu::Column::MenuId)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_menu");
+                ApiErr::ErrSystem(None)
+            })
+    }
+
+    // 查询角色ID -> 功能ID列表
+    async fn role_menu_ids(&self) -> Result<HashMap<i64, Vec<i64>>> {
+        let mut grants: HashMap<i64, Vec<i64>> = HashMap::new();
+        TRoleMenu::find()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_menu");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .for_each(|model| grants.entry(model.role_id).or_default().push(model.menu_id));
+        Ok(grants)
+    }
+}
+
+async fn replace_role_menu(txn: &DatabaseTransaction, role_id: i64, menu_ids: Vec<i64>, now: i64) -> Result<()> {
+    if let Err(e) = TRoleMenu::delete_many()
+        .filter(t_role_menu::Column::RoleId.eq(role_id))
+        .exec(txn)
+        .await
+    {
+        tracing::error!(error = ?e, "error delete t_role_menu");
+        return Err(ApiErr::ErrSystem(None));
+    }
+
+    if menu_ids.is_empty() {
+        return Ok(());
+    }
+    let models = menu_ids.into_iter().map(|menu_id| t_role_menu::ActiveModel {
+        role_id: Set(role_id),
+        menu_id: Set(menu_id),
+        create_time: Set(now),
+        update_time: Set(now),
+        ..Default::default()
+    });
+    if let Err(e) = TRoleMenu::insert_many(models).exec(txn).await {
+        tracing::error!(error = ?e, "error insert t_role_menu");
+        return Err(ApiErr::ErrSystem(None));
+    }
+    Ok(())
+}
+
+// 计算菜单的稳定 key：上级 key + 分隔符 + 菜单名称，同名兄弟菜单按ID顺序追加 #2、#3
+fn menu_keys(models: &[t_menu::Model]) -> HashMap<i64, String> {
+    let by_id: HashMap<i64, &t_menu::Model> = models.iter().map(|model| (model.menu_id, model)).collect();
+    let mut keys: HashMap<i64, String> = HashMap::new();
+    let mut used: HashMap<String, usize> = HashMap::new();
+
+    fn key_of(
+        id: i64,
+        by_id: &HashMap<i64, &t_menu::Model>,
+        keys: &mut HashMap<i64, String>,
+        used: &mut HashMap<String, usize>,
+        visiting: &mut HashSet<i64>,
+    ) -> String {
+        if let Some(key) = keys.get(&id) {
+            return key.clone();
+        }
+        let model = by_id[&id];
+        visiting.insert(id);
+        // 循环引用时不再向上追溯
+        let base = match by_id.contains_key(&model.parent_id) && !visiting.contains(&model.parent_id) {
+            true => format!("{}{}{}", key_of(model.parent_id, by_id, keys, used, visiting), KEY_SEPARATOR, model.menu_name),
+            false => model.menu_name.clone(),
+        };
+        visiting.remove(&id);
+
+        let count = used.entry(base.clone()).or_default();
+        *count += 1;
+        let key = match *count {
+            1 => base,
+            n => format!("{}#{}", base, n),
+        };
+        keys.insert(id, key.clone());
+        key
+    }
+
+    for model in models {
+        key_of(model.menu_id, &by_id, &mut keys, &mut used, &mut HashSet::new());
+    }
+    keys
+}
+
+// 将导入条目排成上级在前的顺序，同时校验 key 唯一且上级菜单存在
+fn sort_entries<'a>(menus: &'a [MenuEntry], existing: &HashMap<&str, &t_menu::Model>) -> Result<Vec<&'a MenuEntry>> {
+    let mut seen = HashSet::new();
+    for entry in menus {
+        if entry.key.is_empty() || entry.menu_name.is_empty() {
+            return Err(ApiErr::ErrParams(Some("菜单 key 与名称不能为空".to_string())));
+        }
+        if !seen.insert(entry.key.as_str()) {
+            return Err(ApiErr::ErrParams(Some(format!("菜单 key 重复：{}", entry.key))));
+        }
+    }
+
+    let mut sorted: Vec<&MenuEntry> = Vec::with_capacity(menus.len());
+    let mut placed: HashSet<&str> = HashSet::new();
+    let mut rest: Vec<&MenuEntry> = menus.iter().collect();
+    while !rest.is_empty() {
+        let (ready, waiting): (Vec<&MenuEntry>, Vec<&MenuEntry>) = rest.into_iter().partition(|entry| {
+            match &entry.parent_key {
+                None => true,
+                Some(key) => placed.contains(key.as_str()) || (existing.contains_key(key.as_str()) && !seen.contains(key.as_str())),
+            }
+        });
+        if ready.is_empty() {
+            let keys: Vec<&str> = waiting.iter().map(|entry| entry.key.as_str()).collect();
+            return Err(ApiErr::ErrParams(Some(format!("上级菜单不存在或存在循环引用：{}", keys.join(", ")))));
+        }
+        placed.extend(ready.iter().map(|entry| entry.key.as_str()));
+        sorted.extend(ready);
+        rest = waiting;
+    }
+    Ok(sorted)
+}
+
+fn to_entry(model: &t_menu::Model, keys: &HashMap<i64, String>) -> MenuEntry {
+    MenuEntry {
+        key: keys[&model.menu_id].clone(),
+        parent_key: keys.get(&model.parent_id).cloned(),
+        menu_name: model.menu_name.clone(),
+        menu_type: model.menu_type,
+        sort: model.sort,
+        path: model.path.clone(),
+        component: model.component.clone(),
+        perms_type: model.perms_type,
+        api_perms: model.api_perms.clone(),
+        web_perms: model.web_perms.clone(),
+        icon: model.icon.clone(),
+        context_menu_key: model.context_menu_id.and_then(|id| keys.get(&id).cloned()),
+        frame_flag: model.frame_flag,
+        frame_url: model.frame_url.clone(),
+        cache_flag: model.cache_flag,
+        visible_flag: model.visible_flag,
+        disabled_flag: model.disabled_flag,
+    }
+}
+
+fn to_active(entry: &MenuEntry, parent_id: i64, context_menu_id: Option<i64>) -> t_menu::ActiveModel {
+    t_menu::ActiveModel {
+        menu_name: Set(entry.menu_name.clone()),
+        menu_type: Set(entry.menu_type),
+        parent_id: Set(parent_id),
+        sort: Set(entry.sort),
+        path: Set(entry.path.clone()),
+        component: Set(entry.component.clone()),
+        perms_type: Set(entry.perms_type),
+        api_perms: Set(entry.api_perms.clone()),
+        web_perms: Set(entry.web_perms.clone()),
+        icon: Set(entry.icon.clone()),
+        context_menu_id: Set(context_menu_id),
+        frame_flag: Set(entry.frame_flag),
+        frame_url: Set(entry.frame_url.clone()),
+        cache_flag: Set(entry.cache_flag),
+        visible_flag: Set(entry.visible_flag),
+        disabled_flag: Set(entry.disabled_flag),
+        ..Default::default()
+    }
+}
+
+// 比较两个条目，返回有差异的字段名
+fn diff(current: &MenuEntry, target: &MenuEntry) -> Vec<&'static str> {
+    macro_rules! changed {
+        ($($field:ident),*) => {{
+            let mut fields = Vec::new();
+            $(
+                if current.$field != target.$field {
+                    fields.push(stringify!($field));
+                }
+            )*
+            fields
+        }};
+    }
+
+    changed!(
+        parent_key, menu_name, menu_type, sort, path, component, perms_type, api_perms,
+        web_perms, icon, context_menu_key, frame_flag, frame_url, cache_flag, visible_flag,
+        disabled_flag
+    )
+}
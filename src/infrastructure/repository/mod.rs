@@ -3,3 +3,6 @@ pub mod login_repository;
 pub mod role_repository;
 pub mod position_repository;
 pub mod employee_repository;
+pub mod employee_history_repository;
+pub mod permission_repository;
+pub mod menu_repository;
\ No newline at end of file
@@ -0,0 +1,222 @@
+use crate::infrastructure::persistence::database as db;
+use crate::infrastructure::security::permission;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use crate::domain::entities::{
+    t_role, prelude::TRole,
+    t_role_employee, prelude::TRoleEmployee,
+    t_role_menu, prelude::TRoleMenu,
+    t_employee,
+    t_menu, prelude::TMenu
+};
+use crate::application::dto::login_dto::RespMenuRoute;
+use crate::common::{
+    result::response::{ApiErr, Result},
+    tree, xtime,
+};
+use time::macros::offset;
+
+// 功能类型：目录、菜单、功能点
+pub const MENU_TYPE_CATALOG: i32 = 1;
+pub const MENU_TYPE_MENU: i32 = 2;
+
+/// 在指定时间生效的角色分配：已到生效时间且未到失效时间，为空表示不限制
+pub fn active_role_employee(now: i64) -> Condition {
+    Condition::all()
+        .add(
+            Condition::any()
+                .add(t_role_employee::Column::ValidFrom.is_null())
+                .add(t_role_employee::Column::ValidFrom.lte(now)),
+        )
+        .add(
+            Condition::any()
+                .add(t_role_employee::Column::ValidUntil.is_null())
+                .add(t_role_employee::Column::ValidUntil.gt(now)),
+        )
+}
+
+pub struct PermissionRepository {
+    conn: DatabaseConnection
+}
+
+impl PermissionRepository {
+    pub fn new() -> Self {
+        Self {
+            conn: db::conn().clone()
+        }
+    }
+
+    // 查询员工当前生效的角色
+    pub async fn employee_roles(&self, employee_id: i64) -> Result<Vec<t_role::Model>> {
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let role_ids = TRoleEmployee::find()
+            .select_only()
+            .column(t_role_employee::Column::RoleId)
+            .filter(t_role_employee::Column::EmployeeId.eq(employee_id))
+            .filter(active_role_employee(now))
+            .into_tuple::<i64>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        if role_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        TRole::find()
+            .filter(t_role::Column::RoleId.is_in(role_ids))
+            .order_by_asc(t_role::Column::RoleId)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })
+    }
+
+    // 查询全部角色的上下级关系
+    pub async fn role_items(&self) -> Result<Vec<tree::Item>> {
+        let roles = TRole::find()
+            .select_only()
+            .column(t_role::Column::RoleId)
+            .column(t_role::Column::RoleName)
+            .column(t_role::Column::ParentId)
+            .into_tuple::<(i64, String, i64)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        Ok(roles
+            .into_iter()
+            .map(|(id, name, parent_id)| tree::Item { id, name, parent_id, sort: 0 })
+            .collect())
+    }
+
+    // 角色自身及其全部上级角色，上级角色的功能权限由下级继承
+    pub async fn effective_role_ids(&self, role_ids: &[i64]) -> Result<Vec<i64>> {
+        if role_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let items = self.role_items().await?;
+        let mut effective: Vec<i64> = role_ids
+            .iter()
+            .flat_map(|role_id| tree::ancestors(&items, *role_id))
+            .collect();
+        effective.sort_unstable();
+        effective.dedup();
+        Ok(effective)
+    }
+
+    // 查询员工可访问的功能ID（超级管理员拥有全部功能）
+    pub async fn employee_menu_ids(&self, employee: &t_employee::Model) -> Result<Vec<i64>> {
+        if let Some(menu_ids) = permission::employee_menu(employee.employee_id) {
+            return Ok(menu_ids);
+        }
+
+        let mut builder = TMenu::find()
+            .select_only()
+            .column(t_menu::Column::MenuId)
+            .filter(t_menu::Column::DeletedFlag.eq(0))
+            .filter(t_menu::Column::DisabledFlag.eq(0));
+
+        if employee.administrator_flag != 1 {
+            let role_ids: Vec<i64> = self
+                .employee_roles(employee.employee_id)
+                .await?
+                .into_iter()
+                .map(|role| role.role_id)
+                .collect();
+            let role_ids = self.effective_role_ids(&role_ids).await?;
+
+            let granted = TRoleMenu::find()
+                .select_only()
+                .column(t_role_menu::Column::MenuId)
+                .filter(t_role_menu::Column::RoleId.is_in(role_ids))
+                .distinct()
+                .into_tuple::<i64>()
+                .all(&self.conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error find t_role_menu");
+                    ApiErr::ErrSystem(None)
+                })?;
+            builder = builder.filter(t_menu::Column::MenuId.is_in(granted));
+        }
+
+        let menu_ids = builder
+            .order_by_asc(t_menu::Column::MenuId)
+            .into_tuple::<i64>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_menu");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        permission::set_employee_menu(employee.employee_id, menu_ids.clone());
+        Ok(menu_ids)
+    }
+
+    // 根据功能ID查询菜单路由树及按钮权限标识
+    pub async fn menu_routes(&self, menu_ids: Vec<i64>) -> Result<(Vec<tree::TreeNode<RespMenuRoute>>, Vec<String>)> {
+        let menus = TMenu::find()
+            .filter(t_menu::Column::MenuId.is_in(menu_ids))
+            .filter(t_menu::Column::DeletedFlag.eq(0))
+            .filter(t_menu::Column::DisabledFlag.eq(0))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_menu");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        // 按钮权限标识不受菜单是否显示影响
+        let mut web_perms: Vec<String> = menus
+            .iter()
+            .filter_map(|menu| menu.web_perms.as_deref())
+            .flat_map(|perms| perms.split(','))
+            .map(|perm| perm.trim().to_string())
+            .filter(|perm| !perm.is_empty())
+            .collect();
+        web_perms.sort();
+        web_perms.dedup();
+
+        // 菜单路由只包含可见的目录和菜单
+        let routes = menus
+            .into_iter()
+            .filter(|menu| menu.visible_flag == 1)
+            .filter(|menu| menu.menu_type == MENU_TYPE_CATALOG || menu.menu_type == MENU_TYPE_MENU)
+            .map(|menu| RespMenuRoute {
+                menu_id: menu.menu_id,
+                menu_name: menu.menu_name,
+                menu_type: menu.menu_type,
+                parent_id: menu.parent_id,
+                path: menu.path,
+                component: menu.component,
+                icon: menu.icon,
+                frame_flag: menu.frame_flag,
+                frame_url: menu.frame_url,
+                cache_flag: menu.cache_flag,
+                sort: menu.sort,
+            })
+            .collect();
+
+        // 上级不可见的菜单一并隐藏
+        let forest = tree::build(routes, tree::Orphans::Drop);
+        if !forest.orphans.is_empty() {
+            tracing::debug!(orphans = ?forest.orphans, "menu routes hidden by invisible parent");
+        }
+        if !forest.cycles.is_empty() {
+            tracing::warn!(cycles = ?forest.cycles, "t_menu parent_id cycle detected");
+        }
+        Ok((forest.roots, web_perms))
+    }
+}
@@ -3,9 +3,11 @@ use crate::infrastructure::persistence::database as db;
 use sea_orm::{
     ColumnTrait, DatabaseConnection, EntityTrait, Order, 
     PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    sea_query::Expr,
 };
 use crate::domain::entities::{
-    t_position, prelude::TPosition
+    t_position, prelude::TPosition,
+    t_employee, prelude::TEmployee,
 };
 use crate::application::dto::position_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelect};
 use crate::common::{
@@ -66,10 +68,10 @@ impl PositionRepository {
 
 
 
-    /** 获取列表 */
-    pub async fn list(&self, query: HashMap<String, String>) -> Result<ApiOK<RespList>> {
+    /** 获取列表，deleted_flag 为 1 时查询已删除职务 */
+    pub async fn list(&self, query: HashMap<String, String>, deleted_flag: u8) -> Result<ApiOK<RespList>> {
         /** 查询条件 */
-        let mut builder = TPosition::find();
+        let mut builder = TPosition::find().filter(t_position::Column::DeletedFlag.eq(deleted_flag));
         if let Some(postname) = query.get("postname") {
             if !postname.is_empty() {
                 builder = builder.filter(t_position::Column::PositionName.contains(postname));
@@ -129,16 +131,9 @@ impl PositionRepository {
 
     /** 获取详情 */
     pub async fn info(&self, postid: u64) -> Result<ApiOK<RespInfo>> {
-        let model = TPosition::find_by_id(postid as i64)
-            .one(&self.conn)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = ?e, "error find t_position");
-                ApiErr::ErrSystem(None)
-            })?
-            .ok_or(ApiErr::ErrNotFound(Some("职务信息不存在".to_string())))?;
+        let model = self.find_position(postid as i64, 0).await?;
 
-        let mut resp = RespInfo {
+        let resp = RespInfo {
             postid: model.position_id,
             postname: model.position_name,
             level: model.level,
@@ -153,9 +148,11 @@ impl PositionRepository {
 
     /** 修改方法 */
     pub async fn update(&self, req: UpdateInfo) -> Result<ApiOK<()>> {
-    
+        self.find_position(req.postid, 0).await?;
+
         let now = xtime::now(offset!(+8)).unix_timestamp();
         let model = t_position::ActiveModel {
+            position_id: Set(req.postid),
             position_name: Set(req.postname),
             level: Set(req.level),
             sort: Set(req.sort),
@@ -171,21 +168,68 @@ impl PositionRepository {
         Ok(ApiOK(None))
     }
 
-    /** 删除 */
+    /** 删除：仍有在职员工使用时不允许删除，否则标记为已删除，已删除员工的职务引用保持有效 */
     pub async fn delete(&self, postid: u64) -> Result<ApiOK<()>> {
+        let model = self.find_position(postid as i64, 0).await?;
 
-        if let Err(e) = TPosition::delete_by_id(postid as i64).exec(&self.conn).await {
-            tracing::error!(error = ?e, "error delete t_position");
-            return Err(ApiErr::ErrSystem(None));
+        let employee_count = TEmployee::find()
+            .filter(t_employee::Column::PositionId.eq(model.position_id))
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .count(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error count t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        if employee_count > 0 {
+            return Err(ApiErr::ErrPerm(Some(format!("该职务下还有 {} 名员工，不能删除", employee_count))));
         }
+
+        self.set_deleted_flag(model.position_id, 1).await?;
+        Ok(ApiOK(None))
+    }
+
+    /** 恢复已删除职务 */
+    pub async fn restore(&self, postid: u64) -> Result<ApiOK<()>> {
+        let model = self.find_position(postid as i64, 1).await?;
+        self.set_deleted_flag(model.position_id, 0).await?;
         Ok(ApiOK(None))
     }
 
+    async fn set_deleted_flag(&self, position_id: i64, deleted_flag: u8) -> Result<()> {
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        if let Err(e) = TPosition::update_many()
+            .col_expr(t_position::Column::DeletedFlag, Expr::value(deleted_flag))
+            .col_expr(t_position::Column::UpdateTime, Expr::value(now))
+            .filter(t_position::Column::PositionId.eq(position_id))
+            .exec(&self.conn)
+            .await
+        {
+            tracing::error!(error = ?e, "error update t_position");
+            return Err(ApiErr::ErrSystem(None));
+        }
+        Ok(())
+    }
+
+    async fn find_position(&self, position_id: i64, deleted_flag: u8) -> Result<t_position::Model> {
+        TPosition::find_by_id(position_id)
+            .filter(t_position::Column::DeletedFlag.eq(deleted_flag))
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_position");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("职务信息不存在".to_string())))
+    }
+
 
     // 获取下拉列表
     pub async fn select_list(&self) -> Result<ApiOK<Vec<RespSelect>>> {
 
         let models = TPosition::find()
+                .filter(t_position::Column::DeletedFlag.eq(0))
+                .order_by(t_position::Column::Sort, Order::Asc)
                 .all(&self.conn)
                 .await
                 .map_err(|e| {
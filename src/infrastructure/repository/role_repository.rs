@@ -1,32 +1,124 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use crate::infrastructure::persistence::database as db;
+use crate::infrastructure::security::permission;
+use crate::infrastructure::repository::permission_repository::{PermissionRepository, active_role_employee};
 use sea_orm::{
     ColumnTrait, DatabaseConnection, EntityTrait, Order, 
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Condition
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, NotSet, Condition,
+    TransactionTrait, ConnectionTrait, sea_query::Expr,
 };
 use crate::domain::entities::{
     t_role, prelude::TRole,
     t_role_employee, prelude::TRoleEmployee,
     t_role_menu, prelude::TRoleMenu,
     t_employee, prelude::TEmployee,
-    t_menu, prelude::TMenu
+    t_menu, prelude::TMenu,
+    t_operate_log, prelude::TOperateLog,
 };
-use crate::application::dto::role_dto::{ReqCreate, RespInfo, RespList, RespRoleMenu, RespMenuSelect, UpdateInfo, RespEmpList, RespEmpInfo, RespSelect};
+use crate::application::dto::role_dto::{ReqCreate, RespInfo, RespList, RespRoleMenu, UpdateInfo, RespEmpList, RespEmpInfo, RespSelect, ReqRoleMenu,
+    ReqRoleEmp, ReqEmpRole, RespRoleEmpChange, RespEffectiveMenu,
+    ReqRoleClone, RespRoleCompare, RespMenuCompare, CompareSide,
+    GrantKind, RespMatrixMenu, RespMatrixEmployee, RespRoleMatrix};
 use crate::common::{
     result::response::{ApiErr, ApiOK, Result},
     utils,xtime,
-    tree,
+    tree, config,
 };
 use time::macros::offset;
 
+// 管理员角色编码的默认值，可由配置 role.admin_code 覆盖
+const DEFAULT_ADMIN_ROLE_CODE: &str = "admin";
+
+// 即将到期提醒窗口：7 天
+const EXPIRING_WINDOW: i64 = 7 * 24 * 3600;
+
+/// 变更后管理员角色须至少保留一名当前生效、未设置失效时间且账号可用的成员
+///
+/// 在写入变更的同一事务中、写入之后调用，返回错误时由调用方回滚；
+/// 加锁读取管理员角色及其成员，并发修改同一角色时串行执行
+pub async fn check_admin_remains<C: ConnectionTrait>(db: &C) -> Result<()> {
+    let now = xtime::now(offset!(+8)).unix_timestamp();
+    let admin_ids = admin_role_ids(db).await?;
+    for role_id in admin_ids {
+        let members = TRoleEmployee::find()
+            .select_only()
+            .column(t_role_employee::Column::EmployeeId)
+            .filter(t_role_employee::Column::RoleId.eq(role_id))
+            .filter(active_role_employee(now))
+            .filter(t_role_employee::Column::ValidUntil.is_null())
+            .lock_shared()
+            .into_tuple::<i64>()
+            .all(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        let count = TEmployee::find()
+            .filter(t_employee::Column::EmployeeId.is_in(members))
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .filter(t_employee::Column::DisabledFlag.eq(0))
+            .lock_shared()
+            .count(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error count t_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        if count == 0 {
+            return Err(ApiErr::ErrPerm(Some("管理员角色至少保留一名长期有效的成员".to_string())));
+        }
+    }
+    Ok(())
+}
+
+/// 员工是否为管理员角色成员（不论有效期）
+pub async fn holds_admin<C: ConnectionTrait>(db: &C, employee_id: i64) -> Result<bool> {
+    let admin_ids = admin_role_ids(db).await?;
+    let count = TRoleEmployee::find()
+        .filter(t_role_employee::Column::RoleId.is_in(admin_ids))
+        .filter(t_role_employee::Column::EmployeeId.eq(employee_id))
+        .count(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error count t_role_employee");
+            ApiErr::ErrSystem(None)
+        })?;
+    Ok(count > 0)
+}
+
+/// 管理员角色编码，该角色至少保留一名成员
+pub fn admin_role_code() -> String {
+    config::global()
+        .get_string("role.admin_code")
+        .unwrap_or_else(|_| DEFAULT_ADMIN_ROLE_CODE.to_string())
+}
+
+async fn admin_role_ids<C: ConnectionTrait>(db: &C) -> Result<Vec<i64>> {
+    TRole::find()
+        .select_only()
+        .column(t_role::Column::RoleId)
+        .filter(t_role::Column::RoleCode.eq(admin_role_code()))
+        .lock_exclusive()
+        .into_tuple::<i64>()
+        .all(db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error find t_role");
+            ApiErr::ErrSystem(None)
+        })
+}
+
 pub struct RoleRepository{
-    conn: DatabaseConnection
+    conn: DatabaseConnection,
+    permission: PermissionRepository,
 }
 
 impl RoleRepository {
     pub fn new() -> Self {
         Self {
-            conn: db::conn().clone()
+            conn: db::conn().clone(),
+            permission: PermissionRepository::new(),
         }
     }
 
@@ -45,12 +137,14 @@ impl RoleRepository {
         if count > 0 {
             return Err(ApiErr::ErrPerm(Some("角色名称或角色编码重复".to_string())));
         }
+        Self::check_parent(&self.conn, 0, req.parentid).await?;
 
         /** 创建数据对象 */
         let now = xtime::now(offset!(+8)).unix_timestamp();
         let model = t_role::ActiveModel {
             role_name: Set(req.rolename),
             role_code: Set(req.rolecode),
+            parent_id: Set(req.parentid),
             remark: Set(req.remark),
             create_time: Set(now),
             ..Default::default()
@@ -114,6 +208,7 @@ impl RoleRepository {
                 roleid: model.role_id,
                 rolename: model.role_name,
                 rolecode: model.role_code,
+                parentid: model.parent_id,
                 remark: model.remark,
                 create_time: model.create_time,
                 create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
@@ -140,6 +235,7 @@ impl RoleRepository {
         roleid: model.role_id,
         rolename: model.role_name,
         rolecode: model.role_code,
+        parentid: model.parent_id,
         remark: model.remark,
         create_time: model.create_time,
         create_time_str: xtime::to_string(xtime::DATETIME, model.create_time, offset!(+8))
@@ -153,6 +249,7 @@ impl RoleRepository {
         /* 判断角色名称或角色编码是否重复*/
         let count = TRole::find()
             .filter(Condition::any().add(t_role::Column::RoleName.eq(req.rolename.clone())).add(t_role::Column::RoleCode.eq(req.rolecode.clone())))
+            .filter(t_role::Column::RoleId.ne(req.roleid))
             .count(&self.conn)
             .await
             .map_err(|e| {
@@ -163,20 +260,40 @@ impl RoleRepository {
         if count > 0 {
             return Err(ApiErr::ErrPerm(Some("角色名称或角色编码重复".to_string())));
         }
+        // 在事务中加锁读取角色上下级关系后校验循环，并发修改上级时串行执行
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        let parent_id = match req.parentid {
+            Some(parentid) => {
+                Self::check_parent(&txn, req.roleid, parentid).await?;
+                Set(parentid)
+            }
+            None => NotSet,
+        };
 
         let now = xtime::now(offset!(+8)).unix_timestamp();
         let model = t_role::ActiveModel {
+            role_id: Set(req.roleid),
             role_name: Set(req.rolename),
             role_code: Set(req.rolecode),
+            parent_id,
             remark: Set(req.remark),
             update_time: Set(now),
             ..Default::default()
         };
 
-        if let Err(e) = TRole::update(model).exec(&self.conn).await {
+        if let Err(e) = TRole::update(model).exec(&txn).await {
             tracing::error!(error = ?e, "error update t_role");
             return Err(ApiErr::ErrSystem(None));
         }
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        // 上级角色可能变化，继承的权限随之失效
+        permission::invalidate_role(req.roleid);
         Ok(ApiOK(None))
     }
 
@@ -195,6 +312,20 @@ impl RoleRepository {
         if count > 0 {
             return Err(ApiErr::ErrPerm(Some("该角色下存在员工，无法删除".to_string())));
         } 
+
+        /* 判断是否存在下级角色 */
+        let child_count = TRole::find()
+        .filter(t_role::Column::ParentId.eq(roleid))
+        .count(&self.conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = ?e, "error count t_role");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        if child_count > 0 {
+            return Err(ApiErr::ErrPerm(Some("该角色下存在下级角色，无法删除".to_string())));
+        }
         if let Err(e) = TRole::delete_by_id(roleid as i64).exec(&self.conn).await {
             tracing::error!(error = ?e, "error delete t_role");
             return Err(ApiErr::ErrSystem(None));
@@ -237,9 +368,8 @@ impl RoleRepository {
         
         let default_roleid = 1;
         // 根据roleid获取角色员工表里的员工ID列表
-        let emp_id_list = TRoleEmployee::find_by_id(roleid.unwrap_or(default_roleid))
-            .select_only()
-            .column(t_role_employee::Column::EmployeeId)
+        let assignments = TRoleEmployee::find()
+            .filter(t_role_employee::Column::RoleId.eq(roleid.unwrap_or(default_roleid)))
             .all(&self.conn)
             .await
             .map_err(|e| {
@@ -247,12 +377,22 @@ impl RoleRepository {
                 ApiErr::ErrSystem(None)
             })?;
 
+        // expiring=1 时仅查询即将到期的成员
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let only_expiring = query.get("expiring").is_some_and(|v| v == "1" || v == "true");
+        let assignments: HashMap<i64, t_role_employee::Model> = assignments
+            .into_iter()
+            .filter(|assignment| !only_expiring || Self::is_expiring(assignment, now))
+            .map(|assignment| (assignment.employee_id, assignment))
+            .collect();
+
         // 将获取的员工ID列表转为Vec<i64>
-        let emp_id_list = emp_id_list.iter().map(|i| i.employee_id).collect::<Vec<_>>();
+        let emp_id_list = assignments.keys().copied().collect::<Vec<_>>();
         
         // 根据员工ID列表封装到查询条件中
         let mut builder = TEmployee::find();
         builder = builder.filter(t_employee::Column::EmployeeId.is_in(emp_id_list));
+        builder = builder.filter(t_employee::Column::DeletedFlag.eq(0));
 
         // 封装查询条件
         if let Some(realname) = query.get("realname") {
@@ -310,7 +450,15 @@ impl RoleRepository {
             list: (Vec::with_capacity(models.len())),
         };
         for model in models {
+            let assignment = assignments.get(&model.employee_id);
+            let valid_until = assignment.and_then(|assignment| assignment.valid_until);
             let info = RespEmpInfo {
+                valid_from: assignment.and_then(|assignment| assignment.valid_from),
+                valid_until,
+                valid_until_str: valid_until
+                    .and_then(|t| xtime::to_string(xtime::DATETIME, t, offset!(+8)).ok())
+                    .unwrap_or_default(),
+                expiring: assignment.is_some_and(|assignment| Self::is_expiring(assignment, now)),
                 employee_id: model.employee_id,
                 login_name: model.login_name,
                 realname: model.realname,
@@ -334,56 +482,988 @@ impl RoleRepository {
 
 
     //查询所有访问资源
-    pub async fn menu_list(&self) -> Result<ApiOK<Vec<tree::TreeNode>>> {
+    pub async fn menu_list(&self) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
         let menu_list = TMenu::find()
         .select_only()
         .column(t_menu::Column::MenuId)
         .column(t_menu::Column::MenuName)
         .column(t_menu::Column::ParentId)
+        .column(t_menu::Column::Sort)
+        .filter(t_menu::Column::DeletedFlag.eq(0))
+        .into_tuple::<(i64, String, i64, Option<i32>)>()
         .all(&self.conn)
         .await
         .map_err(|e| {
-            tracing::error!(error = ?e, "error find t_department");
+            tracing::error!(error = ?e, "error find t_menu");
             ApiErr::ErrSystem(None)
         })?;
 
-        let mut list = Vec::new();
-        for menu in &menu_list{
-            list.push(RespMenuSelect{
-                menu_id:menu.menu_id,
-                menu_name: menu.menu_name.clone(),
-                parent_id: menu.parent_id,
-            });
+        let items = menu_list
+            .into_iter()
+            .map(|(id, name, parent_id, sort)| tree::Item { id, name, parent_id, sort: sort.unwrap_or_default() as i64 })
+            .collect();
+        let forest = tree::build(items, tree::Orphans::Root);
+        if !forest.cycles.is_empty() {
+            tracing::warn!(cycles = ?forest.cycles, "t_menu parent_id cycle detected");
         }
-
-        let tuple_list = list.iter().map(|i| (i.menu_id, i.menu_name.clone(), Some(i.parent_id))).collect::<Vec<_>>();
-        let tuple_node = tree::build_tree(tuple_list);
-        Ok(ApiOK(tuple_node))
+        Ok(ApiOK(Some(forest.roots)))
     }
 
     
 
     //根据角色查询该角色可以访问的资源ID
     pub async fn role_menu(&self, roleid: i64) -> Result<ApiOK<Vec<RespRoleMenu>>> {
-        let role_menu = TRoleMenu::find()
-        .filter(t_role_menu::Column::RoleId.eq(roleid))
-        .select_only()
-        .column(t_role_menu::Column::MenuId)
-        .column(t_role_menu::Column::RoleId)
-        .all(&self.conn)
-        .await
-        .map_err(|e| {
-            tracing::error!(error = ?e, "error find t_role_menu");
+        let menu_ids = match permission::role_menu(roleid) {
+            Some(v) => v,
+            None => {
+                let role_menu = TRoleMenu::find()
+                    .filter(t_role_menu::Column::RoleId.eq(roleid))
+                    .select_only()
+                    .column(t_role_menu::Column::MenuId)
+                    .into_tuple::<i64>()
+                    .all(&self.conn)
+                    .await
+                    .map_err(|e| {
+                        tracing::error!(error = ?e, "error find t_role_menu");
+                        ApiErr::ErrSystem(None)
+                    })?;
+                permission::set_role_menu(roleid, role_menu.clone());
+                role_menu
+            }
+        };
+
+        let list = menu_ids
+            .into_iter()
+            .map(|menu_id| RespRoleMenu { role_id: roleid, menu_id })
+            .collect();
+        Ok(ApiOK(Some(list)))
+    }
+
+    //保存角色的功能权限：整体替换，并自动补全所选功能的上级菜单
+    pub async fn save_role_menu(&self, req: ReqRoleMenu) -> Result<ApiOK<Vec<RespRoleMenu>>> {
+        TRole::find_by_id(req.roleid)
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("角色信息不存在".to_string())))?;
+
+        // 查询所有未删除的功能，用于校验与补全上级菜单
+        let parent_map: HashMap<i64, i64> = TMenu::find()
+            .select_only()
+            .column(t_menu::Column::MenuId)
+            .column(t_menu::Column::ParentId)
+            .filter(t_menu::Column::DeletedFlag.eq(0))
+            .into_tuple::<(i64, i64)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_menu");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect();
+
+        let mut menu_ids: HashSet<i64> = HashSet::new();
+        for menu_id in &req.menu_ids {
+            if !parent_map.contains_key(menu_id) {
+                return Err(ApiErr::ErrParams(Some(format!("功能权限不存在：{}", menu_id))));
+            }
+            // 逐级向上补全，遇到已加入的节点即停止（同时避免脏数据造成死循环）
+            let mut current = *menu_id;
+            while parent_map.contains_key(&current) && menu_ids.insert(current) {
+                current = parent_map[&current];
+            }
+        }
+
+        let mut menu_ids: Vec<i64> = menu_ids.into_iter().collect();
+        menu_ids.sort_unstable();
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        if let Err(e) = TRoleMenu::delete_many()
+            .filter(t_role_menu::Column::RoleId.eq(req.roleid))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error delete t_role_menu");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        if !menu_ids.is_empty() {
+            let models = menu_ids.iter().map(|menu_id| t_role_menu::ActiveModel {
+                role_id: Set(req.roleid),
+                menu_id: Set(*menu_id),
+                create_time: Set(now),
+                update_time: Set(now),
+                ..Default::default()
+            });
+            if let Err(e) = TRoleMenu::insert_many(models).exec(&txn).await {
+                tracing::error!(error = ?e, "error insert t_role_menu");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        // 权限已变更，清除缓存
+        permission::invalidate_role(req.roleid);
+
+        let list = menu_ids
+            .into_iter()
+            .map(|menu_id| RespRoleMenu { role_id: req.roleid, menu_id })
+            .collect();
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 批量添加角色成员，已是成员的员工忽略
+    pub async fn add_role_emp(&self, req: ReqRoleEmp) -> Result<ApiOK<RespRoleEmpChange>> {
+        Self::check_validity(req.valid_from, req.valid_until)?;
+        self.find_role(req.roleid).await?;
+        let employee_ids = self.check_employees(&req.employee_ids).await?;
+
+        // 加锁读取角色与现有成员，并发添加同一角色成员时串行执行，避免重复插入
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        Self::lock_role(&txn, req.roleid).await?;
+        let members = Self::role_members_in(&txn, req.roleid).await?;
+
+        let (skipped, changed): (Vec<i64>, Vec<i64>) = employee_ids
+            .into_iter()
+            .partition(|employee_id| members.contains(employee_id));
+
+        if !changed.is_empty() {
+            let now = xtime::now(offset!(+8)).unix_timestamp();
+            let models = changed.iter().map(|employee_id| t_role_employee::ActiveModel {
+                role_id: Set(req.roleid),
+                employee_id: Set(*employee_id),
+                valid_from: Set(req.valid_from),
+                valid_until: Set(req.valid_until),
+                create_time: Set(now),
+                update_time: Set(now),
+                ..Default::default()
+            });
+            if let Err(e) = TRoleEmployee::insert_many(models).exec(&txn).await {
+                tracing::error!(error = ?e, "error insert t_role_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            txn.commit().await.map_err(|e| {
+                tracing::error!(error = ?e, "error commit transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            changed.iter().for_each(|employee_id| permission::invalidate_employee(*employee_id));
+        }
+
+        Ok(ApiOK(Some(RespRoleEmpChange { changed, skipped })))
+    }
+
+    // 批量修改角色成员的有效期，非成员的员工忽略
+    pub async fn set_role_emp_validity(&self, req: ReqRoleEmp) -> Result<ApiOK<RespRoleEmpChange>> {
+        Self::check_validity(req.valid_from, req.valid_until)?;
+        let role = self.find_role(req.roleid).await?;
+        let members = self.role_members(req.roleid).await?;
+
+        let requested: HashSet<i64> = req.employee_ids.iter().copied().collect();
+        let (changed, skipped): (Vec<i64>, Vec<i64>) = requested
+            .into_iter()
+            .partition(|employee_id| members.contains(employee_id));
+
+        if !changed.is_empty() {
+            let now = xtime::now(offset!(+8)).unix_timestamp();
+            let txn = self.conn.begin().await.map_err(|e| {
+                tracing::error!(error = ?e, "error begin transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            if let Err(e) = TRoleEmployee::update_many()
+                .col_expr(t_role_employee::Column::ValidFrom, Expr::value(req.valid_from))
+                .col_expr(t_role_employee::Column::ValidUntil, Expr::value(req.valid_until))
+                .col_expr(t_role_employee::Column::UpdateTime, Expr::value(now))
+                .filter(t_role_employee::Column::RoleId.eq(req.roleid))
+                .filter(t_role_employee::Column::EmployeeId.is_in(changed.clone()))
+                .exec(&txn)
+                .await
+            {
+                tracing::error!(error = ?e, "error update t_role_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            // 管理员角色须保留一名长期有效的成员
+            if role.role_code == admin_role_code() {
+                check_admin_remains(&txn).await?;
+            }
+            txn.commit().await.map_err(|e| {
+                tracing::error!(error = ?e, "error commit transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            changed.iter().for_each(|employee_id| permission::invalidate_employee(*employee_id));
+        }
+
+        Ok(ApiOK(Some(RespRoleEmpChange { changed, skipped })))
+    }
+
+    // 批量移除角色成员，管理员角色须保留一名长期有效的成员
+    pub async fn remove_role_emp(&self, req: ReqRoleEmp) -> Result<ApiOK<RespRoleEmpChange>> {
+        let role = self.find_role(req.roleid).await?;
+        let members = self.role_members(req.roleid).await?;
+
+        let requested: HashSet<i64> = req.employee_ids.iter().copied().collect();
+        let (changed, skipped): (Vec<i64>, Vec<i64>) = requested
+            .into_iter()
+            .partition(|employee_id| members.contains(employee_id));
+
+        if !changed.is_empty() {
+            let txn = self.conn.begin().await.map_err(|e| {
+                tracing::error!(error = ?e, "error begin transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            if let Err(e) = TRoleEmployee::delete_many()
+                .filter(t_role_employee::Column::RoleId.eq(req.roleid))
+                .filter(t_role_employee::Column::EmployeeId.is_in(changed.clone()))
+                .exec(&txn)
+                .await
+            {
+                tracing::error!(error = ?e, "error delete t_role_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+            if role.role_code == admin_role_code() {
+                check_admin_remains(&txn).await?;
+            }
+            txn.commit().await.map_err(|e| {
+                tracing::error!(error = ?e, "error commit transaction");
+                ApiErr::ErrSystem(None)
+            })?;
+            changed.iter().for_each(|employee_id| permission::invalidate_employee(*employee_id));
+        }
+
+        Ok(ApiOK(Some(RespRoleEmpChange { changed, skipped })))
+    }
+
+    // 查询员工拥有的角色
+    pub async fn emp_roles(&self, employee_id: i64) -> Result<ApiOK<Vec<RespSelect>>> {
+        self.check_employees(&[employee_id]).await?;
+        let role_ids = self.employee_role_ids(employee_id).await?;
+
+        let models = TRole::find()
+            .filter(t_role::Column::RoleId.is_in(role_ids))
+            .order_by(t_role::Column::RoleId, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let list = models
+            .into_iter()
+            .map(|model| RespSelect {
+                roleid: model.role_id,
+                rolename: model.role_name,
+            })
+            .collect();
+        Ok(ApiOK(Some(list)))
+    }
+
+    // 整体替换员工的角色
+    pub async fn save_emp_roles(&self, req: ReqEmpRole) -> Result<ApiOK<Vec<RespSelect>>> {
+        self.check_employees(&[req.employee_id]).await?;
+
+        let role_ids: HashSet<i64> = req.role_ids.iter().copied().collect();
+        let roles = TRole::find()
+            .filter(t_role::Column::RoleId.is_in(role_ids.clone()))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?;
+        if roles.len() != role_ids.len() {
+            return Err(ApiErr::ErrParams(Some("角色信息不存在".to_string())));
+        }
+
+        // 保留的角色沿用原有效期
+        let validity: HashMap<i64, (Option<i64>, Option<i64>)> = TRoleEmployee::find()
+            .filter(t_role_employee::Column::EmployeeId.eq(req.employee_id))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .map(|assignment| (assignment.role_id, (assignment.valid_from, assignment.valid_until)))
+            .collect();
+
+        // 从管理员角色中移除时，写入后校验该角色仍有长期有效的成员
+        let current: HashSet<i64> = validity.keys().copied().collect();
+        let removed: Vec<i64> = current.difference(&role_ids).copied().collect();
+        let removes_admin = TRole::find()
+            .filter(t_role::Column::RoleId.is_in(removed))
+            .filter(t_role::Column::RoleCode.eq(admin_role_code()))
+            .count(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error count t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            > 0;
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
             ApiErr::ErrSystem(None)
         })?;
 
-        let mut list = Vec::new();
-        for menu in &role_menu{
-            list.push(RespRoleMenu{
-                role_id: menu.role_id,
-                menu_id: menu.menu_id,
+        if let Err(e) = TRoleEmployee::delete_many()
+            .filter(t_role_employee::Column::EmployeeId.eq(req.employee_id))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error delete t_role_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        if !role_ids.is_empty() {
+            let models = role_ids.iter().map(|role_id| {
+                let (valid_from, valid_until) = validity.get(role_id).copied().unwrap_or_default();
+                t_role_employee::ActiveModel {
+                    role_id: Set(*role_id),
+                    employee_id: Set(req.employee_id),
+                    valid_from: Set(valid_from),
+                    valid_until: Set(valid_until),
+                    create_time: Set(now),
+                    update_time: Set(now),
+                    ..Default::default()
+                }
             });
+            if let Err(e) = TRoleEmployee::insert_many(models).exec(&txn).await {
+                tracing::error!(error = ?e, "error insert t_role_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+        if removes_admin {
+            check_admin_remains(&txn).await?;
         }
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        permission::invalidate_employee(req.employee_id);
+
+        self.emp_roles(req.employee_id).await
+    }
+
+    // 角色树
+    pub async fn role_tree(&self) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
+        let items = self.permission.role_items().await?;
+        let forest = tree::build(items, tree::Orphans::Root);
+        if !forest.cycles.is_empty() {
+            tracing::warn!(cycles = ?forest.cycles, "t_role parent_id cycle detected");
+        }
+        Ok(ApiOK(Some(forest.roots)))
+    }
+
+    // 角色的有效功能权限：自身授权及从上级角色继承的授权
+    pub async fn role_menu_effective(&self, roleid: i64) -> Result<ApiOK<Vec<RespEffectiveMenu>>> {
+        self.find_role(roleid).await?;
+        let sources = self.effective_menus(roleid).await?;
+
+        let mut list: Vec<RespEffectiveMenu> = sources
+            .into_iter()
+            .map(|(menu_id, source_role_id)| RespEffectiveMenu {
+                menu_id,
+                source_role_id,
+                inherited: source_role_id != roleid,
+            })
+            .collect();
+        list.sort_by_key(|item| item.menu_id);
         Ok(ApiOK(Some(list)))
     }
+
+    // 复制角色：名称与编码追加后缀，复制备注、上级角色与功能权限，可选复制成员
+    pub async fn clone_role(&self, req: ReqRoleClone) -> Result<ApiOK<RespInfo>> {
+        let source = self.find_role(req.roleid).await?;
+        let role_name = format!("{}{}", source.role_name, req.suffix);
+        let role_code = format!("{}{}", source.role_code, req.suffix);
+
+        let count = TRole::find()
+            .filter(Condition::any().add(t_role::Column::RoleName.eq(role_name.clone())).add(t_role::Column::RoleCode.eq(role_code.clone())))
+            .count(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error count t_role");
+                ApiErr::ErrSystem(None)
+            })?;
+        if count > 0 {
+            return Err(ApiErr::ErrPerm(Some("角色名称或角色编码重复".to_string())));
+        }
+
+        let grants = TRoleMenu::find()
+            .filter(t_role_menu::Column::RoleId.eq(req.roleid))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_menu");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let members = if req.with_members {
+            TRoleEmployee::find()
+                .filter(t_role_employee::Column::RoleId.eq(req.roleid))
+                .all(&self.conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!(error = ?e, "error find t_role_employee");
+                    ApiErr::ErrSystem(None)
+                })?
+        } else {
+            Vec::new()
+        };
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        let model = t_role::ActiveModel {
+            role_name: Set(role_name),
+            role_code: Set(role_code),
+            parent_id: Set(source.parent_id),
+            remark: Set(source.remark),
+            create_time: Set(now),
+            update_time: Set(now),
+            ..Default::default()
+        };
+        let roleid = match TRole::insert(model).exec(&txn).await {
+            Ok(v) => v.last_insert_id,
+            Err(e) => {
+                tracing::error!(error = ?e, "error insert t_role");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        };
+
+        if !grants.is_empty() {
+            let models = grants.iter().map(|grant| t_role_menu::ActiveModel {
+                role_id: Set(roleid),
+                menu_id: Set(grant.menu_id),
+                create_time: Set(now),
+                update_time: Set(now),
+                ..Default::default()
+            });
+            if let Err(e) = TRoleMenu::insert_many(models).exec(&txn).await {
+                tracing::error!(error = ?e, "error insert t_role_menu");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+
+        if !members.is_empty() {
+            let models = members.iter().map(|member| t_role_employee::ActiveModel {
+                role_id: Set(roleid),
+                employee_id: Set(member.employee_id),
+                valid_from: Set(member.valid_from),
+                valid_until: Set(member.valid_until),
+                create_time: Set(now),
+                update_time: Set(now),
+                ..Default::default()
+            });
+            if let Err(e) = TRoleEmployee::insert_many(models).exec(&txn).await {
+                tracing::error!(error = ?e, "error insert t_role_employee");
+                return Err(ApiErr::ErrSystem(None));
+            }
+        }
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+        members.iter().for_each(|member| permission::invalidate_employee(member.employee_id));
+
+        self.info(roleid as u64).await
+    }
+
+    // 对比两个角色的有效功能权限，以功能树返回，仅一方拥有的功能保留上级路径
+    pub async fn compare_roles(&self, role_a: i64, role_b: i64) -> Result<ApiOK<RespRoleCompare>> {
+        let model_a = self.find_role(role_a).await?;
+        let model_b = self.find_role(role_b).await?;
+        let menus_a: HashSet<i64> = self.effective_menus(role_a).await?.into_keys().collect();
+        let menus_b: HashSet<i64> = self.effective_menus(role_b).await?.into_keys().collect();
+
+        let mut only_a: Vec<i64> = menus_a.difference(&menus_b).copied().collect();
+        let mut only_b: Vec<i64> = menus_b.difference(&menus_a).copied().collect();
+        let mut both: Vec<i64> = menus_a.intersection(&menus_b).copied().collect();
+        only_a.sort_unstable();
+        only_b.sort_unstable();
+        both.sort_unstable();
+
+        let menus = TMenu::find()
+            .filter(t_menu::Column::DeletedFlag.eq(0))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_menu");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let items: Vec<RespMenuCompare> = menus
+            .into_iter()
+            .map(|menu| {
+                let side = match (menus_a.contains(&menu.menu_id), menus_b.contains(&menu.menu_id)) {
+                    (true, true) => CompareSide::Both,
+                    (true, false) => CompareSide::OnlyA,
+                    (false, true) => CompareSide::OnlyB,
+                    (false, false) => CompareSide::None,
+                };
+                RespMenuCompare {
+                    menu_id: menu.menu_id,
+                    menu_name: menu.menu_name,
+                    menu_type: menu.menu_type,
+                    web_perms: menu.web_perms,
+                    side,
+                    parent_id: menu.parent_id,
+                    sort: menu.sort.unwrap_or_default() as i64,
+                }
+            })
+            .collect();
+        let roots = tree::build(items, tree::Orphans::Root).roots;
+        let tree = tree::prune(roots, &|menu: &RespMenuCompare| menu.side != CompareSide::None);
+
+        Ok(ApiOK(Some(RespRoleCompare {
+            role_a: RespSelect { roleid: model_a.role_id, rolename: model_a.role_name },
+            role_b: RespSelect { roleid: model_b.role_id, rolename: model_b.role_name },
+            only_a,
+            only_b,
+            both,
+            tree,
+        })))
+    }
+
+    // 角色 × 功能权限矩阵，以及全部员工当前生效的角色
+    pub async fn permission_matrix(&self) -> Result<RespRoleMatrix> {
+        let role_items = self.permission.role_items().await?;
+        let mut roles: Vec<RespSelect> = role_items
+            .iter()
+            .map(|item| RespSelect { roleid: item.id, rolename: item.name.clone() })
+            .collect();
+        roles.sort_by_key(|role| role.roleid);
+        let role_names: HashMap<i64, String> = role_items.iter().map(|item| (item.id, item.name.clone())).collect();
+
+        let mut direct: HashMap<i64, HashSet<i64>> = HashMap::new();
+        TRoleMenu::find()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_menu");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .for_each(|grant| {
+                direct.entry(grant.role_id).or_default().insert(grant.menu_id);
+            });
+
+        // 每个角色的上级角色链（不含自身）
+        let chains: HashMap<i64, Vec<i64>> = roles
+            .iter()
+            .map(|role| {
+                let mut chain = tree::ancestors(&role_items, role.roleid);
+                chain.pop();
+                (role.roleid, chain)
+            })
+            .collect();
+
+        let menus = TMenu::find()
+            .filter(t_menu::Column::DeletedFlag.eq(0))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_menu");
+                ApiErr::ErrSystem(None)
+            })?;
+        let details: HashMap<i64, (i32, Option<String>)> = menus
+            .iter()
+            .map(|menu| (menu.menu_id, (menu.menu_type, menu.web_perms.clone())))
+            .collect();
+        let items: Vec<tree::Item> = menus
+            .into_iter()
+            .map(|menu| tree::Item {
+                id: menu.menu_id,
+                name: menu.menu_name,
+                parent_id: menu.parent_id,
+                sort: menu.sort.unwrap_or_default() as i64,
+            })
+            .collect();
+        let roots = tree::build(items, tree::Orphans::Root).roots;
+
+        let mut path: Vec<&str> = Vec::new();
+        let mut rows = Vec::new();
+        for (depth, item) in tree::flatten(&roots) {
+            path.truncate(depth);
+            path.push(&item.name);
+
+            let grants = roles
+                .iter()
+                .map(|role| {
+                    let has = |role_id: &i64| direct.get(role_id).is_some_and(|menu_ids| menu_ids.contains(&item.id));
+                    if has(&role.roleid) {
+                        GrantKind::Direct
+                    } else if chains[&role.roleid].iter().any(has) {
+                        GrantKind::Inherited
+                    } else {
+                        GrantKind::None
+                    }
+                })
+                .collect();
+            let (menu_type, web_perms) = details.get(&item.id).cloned().unwrap_or_default();
+            rows.push(RespMatrixMenu {
+                menu_id: item.id,
+                path: path.join("/"),
+                menu_type,
+                web_perms,
+                grants,
+            });
+        }
+
+        let now = xtime::now(offset!(+8)).unix_timestamp();
+        let mut assignments: HashMap<i64, Vec<i64>> = HashMap::new();
+        TRoleEmployee::find()
+            .filter(active_role_employee(now))
+            .order_by(t_role_employee::Column::RoleId, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .for_each(|assignment| assignments.entry(assignment.employee_id).or_default().push(assignment.role_id));
+
+        let employees = TEmployee::find()
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .order_by(t_employee::Column::EmployeeId, Order::Asc)
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .map(|employee| {
+                let role_ids = assignments.remove(&employee.employee_id).unwrap_or_default();
+                let mut inherited: Vec<i64> = Vec::new();
+                for role_id in &role_ids {
+                    for parent_id in chains.get(role_id).into_iter().flatten() {
+                        if !role_ids.contains(parent_id) && !inherited.contains(parent_id) {
+                            inherited.push(*parent_id);
+                        }
+                    }
+                }
+                let names = |ids: &[i64]| ids.iter().filter_map(|id| role_names.get(id).cloned()).collect();
+                RespMatrixEmployee {
+                    employee_id: employee.employee_id,
+                    login_name: employee.login_name,
+                    realname: employee.realname,
+                    administrator_flag: employee.administrator_flag,
+                    roles: names(&role_ids),
+                    inherited_roles: names(&inherited),
+                }
+            })
+            .collect();
+
+        Ok(RespRoleMatrix { roles, menus: rows, employees })
+    }
+
+    // 有效功能权限：功能ID -> 授权来源角色，离角色越近的来源优先
+    async fn effective_menus(&self, roleid: i64) -> Result<HashMap<i64, i64>> {
+        let items = self.permission.role_items().await?;
+        // 自下而上
+        let chain: Vec<i64> = tree::ancestors(&items, roleid).into_iter().rev().collect();
+
+        let grants = TRoleMenu::find()
+            .filter(t_role_menu::Column::RoleId.is_in(chain.clone()))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_menu");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let mut sources: HashMap<i64, i64> = HashMap::new();
+        for role_id in &chain {
+            for grant in grants.iter().filter(|grant| grant.role_id == *role_id) {
+                sources.entry(grant.menu_id).or_insert(*role_id);
+            }
+        }
+        Ok(sources)
+    }
+
+    // 校验上级角色：必须存在，且不能是自身或自身的下级角色
+    async fn check_parent<C: ConnectionTrait>(db: &C, roleid: i64, parentid: i64) -> Result<()> {
+        if parentid == 0 {
+            return Ok(());
+        }
+        if parentid == roleid {
+            return Err(ApiErr::ErrParams(Some("上级角色不能是自身".to_string())));
+        }
+
+        let items: Vec<tree::Item> = TRole::find()
+            .select_only()
+            .column(t_role::Column::RoleId)
+            .column(t_role::Column::ParentId)
+            .lock_exclusive()
+            .into_tuple::<(i64, i64)>()
+            .all(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .map(|(id, parent_id)| tree::Item { id, name: String::new(), parent_id, sort: 0 })
+            .collect();
+        if !items.iter().any(|item| item.id == parentid) {
+            return Err(ApiErr::ErrParams(Some("上级角色不存在".to_string())));
+        }
+        if roleid != 0 && tree::ancestors(&items, parentid).contains(&roleid) {
+            return Err(ApiErr::ErrParams(Some("上级角色不能是自身的下级角色".to_string())));
+        }
+        Ok(())
+    }
+
+    // 清理到期的角色成员并记录操作日志，返回移除数量
+    // since 之后开始生效的成员同样需要清除权限缓存
+    pub async fn sweep_role_emp(&self, since: i64, now: i64) -> Result<usize> {
+        let expired = TRoleEmployee::find()
+            .filter(t_role_employee::Column::ValidUntil.lte(now))
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+
+        let activated = TRoleEmployee::find()
+            .select_only()
+            .column(t_role_employee::Column::EmployeeId)
+            .filter(t_role_employee::Column::ValidFrom.gt(since))
+            .filter(t_role_employee::Column::ValidFrom.lte(now))
+            .into_tuple::<i64>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        activated.into_iter().for_each(permission::invalidate_employee);
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let role_names: HashMap<i64, String> = TRole::find()
+            .select_only()
+            .column(t_role::Column::RoleId)
+            .column(t_role::Column::RoleName)
+            .filter(t_role::Column::RoleId.is_in(expired.iter().map(|assignment| assignment.role_id)))
+            .into_tuple::<(i64, String)>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect();
+
+        let txn = self.conn.begin().await.map_err(|e| {
+            tracing::error!(error = ?e, "error begin transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        // 管理员角色已没有长期有效的成员时保留其到期记录以便恢复，其余到期成员照常移除
+        let admin_ids = admin_role_ids(&txn).await?;
+        let mut expired = expired;
+        if expired.iter().any(|assignment| admin_ids.contains(&assignment.role_id)) {
+            if let Err(e) = check_admin_remains(&txn).await {
+                if !matches!(e, ApiErr::ErrPerm(_)) {
+                    return Err(e);
+                }
+                tracing::warn!("admin role has no permanent active member, keeping its expired assignments");
+                expired.retain(|assignment| !admin_ids.contains(&assignment.role_id));
+            }
+        }
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        if let Err(e) = TRoleEmployee::delete_many()
+            .filter(t_role_employee::Column::Id.is_in(expired.iter().map(|assignment| assignment.id)))
+            .exec(&txn)
+            .await
+        {
+            tracing::error!(error = ?e, "error delete t_role_employee");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        let logs = expired.iter().map(|assignment| {
+            let valid_until = assignment.valid_until.unwrap_or_default();
+            t_operate_log::ActiveModel {
+                operate_user_id: Set(0),
+                operate_user_type: Set(0),
+                operate_user_name: Set("system".to_string()),
+                module: Set(Some("角色管理".to_string())),
+                content: Set(Some(format!(
+                    "角色成员到期移除：角色[{}]，员工ID {}，失效时间 {}",
+                    role_names.get(&assignment.role_id).cloned().unwrap_or_default(),
+                    assignment.employee_id,
+                    xtime::to_string(xtime::DATETIME, valid_until, offset!(+8)).unwrap_or_default(),
+                ))),
+                param: Set(Some(
+                    serde_json::json!({
+                        "role_id": assignment.role_id,
+                        "employee_id": assignment.employee_id,
+                        "valid_from": assignment.valid_from,
+                        "valid_until": assignment.valid_until,
+                    })
+                    .to_string(),
+                )),
+                success_flag: Set(Some(1)),
+                create_time: Set(now),
+                update_time: Set(now),
+                ..Default::default()
+            }
+        });
+        if let Err(e) = TOperateLog::insert_many(logs).exec(&txn).await {
+            tracing::error!(error = ?e, "error insert t_operate_log");
+            return Err(ApiErr::ErrSystem(None));
+        }
+
+        txn.commit().await.map_err(|e| {
+            tracing::error!(error = ?e, "error commit transaction");
+            ApiErr::ErrSystem(None)
+        })?;
+
+        expired.iter().for_each(|assignment| permission::invalidate_employee(assignment.employee_id));
+        Ok(expired.len())
+    }
+
+    fn is_expiring(assignment: &t_role_employee::Model, now: i64) -> bool {
+        assignment
+            .valid_until
+            .is_some_and(|valid_until| valid_until > now && valid_until <= now + EXPIRING_WINDOW)
+    }
+
+    // 校验有效期：失效时间须晚于生效时间与当前时间
+    fn check_validity(valid_from: Option<i64>, valid_until: Option<i64>) -> Result<()> {
+        if let Some(valid_until) = valid_until {
+            if valid_from.is_some_and(|valid_from| valid_from >= valid_until) {
+                return Err(ApiErr::ErrParams(Some("失效时间必须晚于生效时间".to_string())));
+            }
+            if valid_until <= xtime::now(offset!(+8)).unix_timestamp() {
+                return Err(ApiErr::ErrParams(Some("失效时间必须晚于当前时间".to_string())));
+            }
+        }
+        Ok(())
+    }
+
+    async fn find_role(&self, roleid: i64) -> Result<t_role::Model> {
+        TRole::find_by_id(roleid)
+            .one(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("角色信息不存在".to_string())))
+    }
+
+    // 在事务中锁定角色行，修改同一角色成员的事务依次执行
+    async fn lock_role<C: ConnectionTrait>(db: &C, roleid: i64) -> Result<t_role::Model> {
+        TRole::find_by_id(roleid)
+            .lock_exclusive()
+            .one(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role");
+                ApiErr::ErrSystem(None)
+            })?
+            .ok_or(ApiErr::ErrNotFound(Some("角色信息不存在".to_string())))
+    }
+
+    // 校验员工是否存在，返回去重后的员工ID
+    async fn check_employees(&self, employee_ids: &[i64]) -> Result<Vec<i64>> {
+        let mut unique: Vec<i64> = Vec::with_capacity(employee_ids.len());
+        for employee_id in employee_ids {
+            if !unique.contains(employee_id) {
+                unique.push(*employee_id);
+            }
+        }
+
+        let found: HashSet<i64> = TEmployee::find()
+            .select_only()
+            .column(t_employee::Column::EmployeeId)
+            .filter(t_employee::Column::EmployeeId.is_in(unique.clone()))
+            .filter(t_employee::Column::DeletedFlag.eq(0))
+            .into_tuple::<i64>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_employee");
+                ApiErr::ErrSystem(None)
+            })?
+            .into_iter()
+            .collect();
+
+        let missing: Vec<String> = unique
+            .iter()
+            .filter(|employee_id| !found.contains(employee_id))
+            .map(|employee_id| employee_id.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(ApiErr::ErrParams(Some(format!("员工信息不存在：{}", missing.join(",")))));
+        }
+        Ok(unique)
+    }
+
+    async fn role_members(&self, roleid: i64) -> Result<HashSet<i64>> {
+        Self::role_members_in(&self.conn, roleid).await
+    }
+
+    async fn role_members_in<C: ConnectionTrait>(db: &C, roleid: i64) -> Result<HashSet<i64>> {
+        let members = TRoleEmployee::find()
+            .select_only()
+            .column(t_role_employee::Column::EmployeeId)
+            .filter(t_role_employee::Column::RoleId.eq(roleid))
+            .into_tuple::<i64>()
+            .all(db)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })?;
+        Ok(members.into_iter().collect())
+    }
+
+    async fn employee_role_ids(&self, employee_id: i64) -> Result<Vec<i64>> {
+        TRoleEmployee::find()
+            .select_only()
+            .column(t_role_employee::Column::RoleId)
+            .filter(t_role_employee::Column::EmployeeId.eq(employee_id))
+            .into_tuple::<i64>()
+            .all(&self.conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = ?e, "error find t_role_employee");
+                ApiErr::ErrSystem(None)
+            })
+    }
 }
@@ -1 +1,2 @@
-pub mod identity;
\ No newline at end of file
+pub mod identity;
+pub mod permission;
\ No newline at end of file
@@ -0,0 +1,107 @@
+// src/infrastructure/security/permission.rs
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// 角色功能权限缓存：角色ID -> 功能ID列表
+static ROLE_MENU: OnceLock<RwLock<HashMap<i64, Vec<i64>>>> = OnceLock::new();
+
+/// 员工有效功能权限缓存：员工ID -> 功能ID列表
+static EMPLOYEE_MENU: OnceLock<RwLock<HashMap<i64, Vec<i64>>>> = OnceLock::new();
+
+fn role_menu_cache() -> &'static RwLock<HashMap<i64, Vec<i64>>> {
+    ROLE_MENU.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn employee_menu_cache() -> &'static RwLock<HashMap<i64, Vec<i64>>> {
+    EMPLOYEE_MENU.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// 读取角色的功能ID缓存
+pub fn role_menu(role_id: i64) -> Option<Vec<i64>> {
+    role_menu_cache()
+        .read()
+        .ok()
+        .and_then(|cache| cache.get(&role_id).cloned())
+}
+
+/// 写入角色的功能ID缓存
+pub fn set_role_menu(role_id: i64, menu_ids: Vec<i64>) {
+    if let Ok(mut cache) = role_menu_cache().write() {
+        cache.insert(role_id, menu_ids);
+    }
+}
+
+/// 读取员工的有效功能ID缓存
+pub fn employee_menu(employee_id: i64) -> Option<Vec<i64>> {
+    employee_menu_cache()
+        .read()
+        .ok()
+        .and_then(|cache| cache.get(&employee_id).cloned())
+}
+
+/// 写入员工的有效功能ID缓存
+pub fn set_employee_menu(employee_id: i64, menu_ids: Vec<i64>) {
+    if let Ok(mut cache) = employee_menu_cache().write() {
+        cache.insert(employee_id, menu_ids);
+    }
+}
+
+/// 员工角色变更后清除该员工的缓存
+pub fn invalidate_employee(employee_id: i64) {
+    if let Ok(mut cache) = employee_menu_cache().write() {
+        cache.remove(&employee_id);
+    }
+}
+
+/// 角色权限变更后清除该角色的缓存，角色下员工的有效权限随之失效
+pub fn invalidate_role(role_id: i64) {
+    if let Ok(mut cache) = role_menu_cache().write() {
+        cache.remove(&role_id);
+    }
+    if let Ok(mut cache) = employee_menu_cache().write() {
+        cache.clear();
+    }
+}
+
+/// 菜单变更（新增、修改、禁用、删除）后清除全部缓存
+pub fn invalidate_all() {
+    if let Ok(mut cache) = role_menu_cache().write() {
+        cache.clear();
+    }
+    if let Ok(mut cache) = employee_menu_cache().write() {
+        cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 缓存为全局状态，放在同一个用例中避免并行测试相互干扰
+    #[test]
+    fn test_permission_cache() {
+        assert!(role_menu(-1).is_none());
+
+        set_role_menu(-1, vec![1, 2, 3]);
+        assert_eq!(role_menu(-1), Some(vec![1, 2, 3]));
+
+        invalidate_role(-1);
+        assert!(role_menu(-1).is_none());
+
+        set_employee_menu(-2, vec![4, 5]);
+        assert_eq!(employee_menu(-2), Some(vec![4, 5]));
+
+        invalidate_employee(-2);
+        assert!(employee_menu(-2).is_none());
+
+        set_employee_menu(-2, vec![4, 5]);
+        invalidate_role(-1);
+        assert!(employee_menu(-2).is_none());
+
+        set_role_menu(-1, vec![1, 2, 3]);
+        set_employee_menu(-2, vec![4, 5]);
+        invalidate_all();
+        assert!(role_menu(-1).is_none());
+        assert!(employee_menu(-2).is_none());
+    }
+}
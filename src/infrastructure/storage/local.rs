@@ -0,0 +1,74 @@
+// src/infrastructure/storage/local.rs
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{bail, Context, Result};
+
+use super::Storage;
+
+/// 本地文件系统存储
+pub struct LocalStorage {
+    root: PathBuf,
+    url_prefix: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, url_prefix: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            url_prefix: url_prefix.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// key 只允许普通路径段，防止越出存储目录
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            bail!("非法的文件路径：{}", key);
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put(&self, key: &str, data: &[u8]) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("创建目录失败：{}", parent.display()))?;
+        }
+        fs::write(&path, data).with_context(|| format!("写入文件失败：{}", path.display()))
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("读取文件失败：{}", path.display())),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.url_prefix, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path(), "/v1/files/");
+
+        storage.put("avatar/1/a.png", b"data").unwrap();
+        assert_eq!(storage.get("avatar/1/a.png").unwrap(), Some(b"data".to_vec()));
+        assert_eq!(storage.get("avatar/1/b.png").unwrap(), None);
+        assert_eq!(storage.url("avatar/1/a.png"), "/v1/files/avatar/1/a.png");
+
+        assert!(storage.put("../a.png", b"data").is_err());
+        assert!(storage.get("/etc/passwd").is_err());
+        assert!(storage.get("").is_err());
+    }
+}
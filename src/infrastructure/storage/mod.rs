@@ -0,0 +1,33 @@
+// src/infrastructure/storage/mod.rs
+pub mod local;
+
+use std::sync::{Arc, OnceLock};
+use anyhow::Result;
+
+use crate::common::config;
+use local::LocalStorage;
+
+/// 文件存储，key 为以 `/` 分隔的相对路径
+pub trait Storage: Send + Sync {
+    /// 写入文件，已存在时覆盖
+    fn put(&self, key: &str, data: &[u8]) -> Result<()>;
+    /// 读取文件，不存在时返回 None
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    /// 文件的访问地址
+    fn url(&self, key: &str) -> String;
+}
+
+static STORAGE: OnceLock<Arc<dyn Storage>> = OnceLock::new();
+
+/// 全局存储，按配置 storage.dir 与 storage.url_prefix 初始化本地存储
+pub fn global() -> Arc<dyn Storage> {
+    STORAGE
+        .get_or_init(|| {
+            let cfg = config::global();
+            Arc::new(LocalStorage::new(
+                cfg.get_string("storage.dir").unwrap_or_else(|_| "uploads".to_string()),
+                cfg.get_string("storage.url_prefix").unwrap_or_else(|_| "/v1/files".to_string()),
+            ))
+        })
+        .clone()
+}
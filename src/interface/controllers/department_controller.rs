@@ -3,7 +3,9 @@ use crate::common::result::{
     rejection::IRejection,
     response::{ApiErr, ApiOK, Result},
 };
-use crate::application::dto::department_dto::{ReqCreate, UpdateInfo, RespInfo, RespList};
+use crate::application::dto::department_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSearch,
+    ReqMove, ReqReorder, RespPathNode, RespReport,
+    ReqMerge, RespMerge};
 use crate::infrastructure::security::identity::Identity;
 use axum::{
     extract::{Path, Query},
@@ -13,6 +15,8 @@ use std::collections::HashMap;
 use axum_extra::extract::WithRejection;
 use validator::Validate;
 use crate::common::tree;
+use crate::common::orgchart::ChartFormat;
+use axum::response::{IntoResponse, Response};
 use std::sync::Arc;
 
 pub struct DepartmentController;
@@ -53,6 +57,15 @@ impl DepartmentController {
         service.list(query).await
     }
 
+    // 模糊搜索，支持拼音全拼与首字母，参数 keyword、limit
+    pub async fn search(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiOK<Vec<RespSearch>>> {
+        service.search(query).await
+    }
+
 
     pub async fn update(
         Extension(service): Extension<Arc<DepartmentService>>,
@@ -77,7 +90,112 @@ impl DepartmentController {
     pub async fn select_list(
         Extension(service): Extension<Arc<DepartmentService>>,
         Extension(identity): Extension<Identity>
-    ) -> Result<ApiOK<Vec<tree::TreeNode>>>{
+    ) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>>{
         service.select_list().await
     }
-}
\ No newline at end of file
+
+    // 移动部门（调整上级及位置）
+    pub async fn move_department(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqMove>>,
+    ) -> Result<ApiOK<()>> {
+        service.move_department(req).await
+    }
+
+    // 拖拽排序同级部门
+    pub async fn reorder(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqReorder>>,
+    ) -> Result<ApiOK<()>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.reorder(req).await
+    }
+
+    // 上级部门链，从顶级部门到自身
+    pub async fn ancestors(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        Path(department_id): Path<i64>,
+    ) -> Result<ApiOK<Vec<RespPathNode>>> {
+        service.ancestors(department_id).await
+    }
+
+    // 所有下级部门，depth 限制相对层数
+    pub async fn descendants(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        Path(department_id): Path<i64>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiOK<Vec<RespPathNode>>> {
+        let depth = match query.get("depth") {
+            Some(v) => Some(v.parse::<i32>().map_err(|_| ApiErr::ErrParams(Some("层数格式错误".to_string())))?),
+            None => None,
+        };
+        service.descendants(department_id, depth).await
+    }
+
+    // 我负责的部门
+    pub async fn managed(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+    ) -> Result<ApiOK<Vec<RespInfo>>> {
+        service.managed(identity.id()).await
+    }
+
+    // 我的直接下属
+    pub async fn direct_reports(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+    ) -> Result<ApiOK<Vec<RespReport>>> {
+        service.direct_reports(identity.id()).await
+    }
+
+    // 我的完整汇报树
+    pub async fn reporting_tree(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+    ) -> Result<ApiOK<Vec<tree::TreeNode<RespReport>>>> {
+        service.reporting_tree(identity.id()).await
+    }
+
+    // 组织架构图，参数 format=json|dot|mermaid、root（根部门ID）、depth（层数）、employees=1（附带员工姓名）
+    // json 直接返回树形数据，dot/mermaid 返回文本文件
+    pub async fn org_chart(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<Response> {
+        let format = ChartFormat::parse(query.get("format").map(String::as_str).unwrap_or("json"))
+            .map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?;
+        let root = match query.get("root") {
+            Some(v) => Some(v.parse::<i64>().map_err(|_| ApiErr::ErrParams(Some("根部门ID格式错误".to_string())))?),
+            None => None,
+        };
+        let depth = match query.get("depth") {
+            Some(v) => Some(v.parse::<usize>().map_err(|_| ApiErr::ErrParams(Some("层数格式错误".to_string())))?),
+            None => None,
+        };
+        let employees = query.get("employees").is_some_and(|v| v == "1" || v == "true");
+
+        match format {
+            ChartFormat::Json => Ok(ApiOK(Some(service.org_chart(root, depth, employees).await?)).into_response()),
+            _ => Ok(service.org_chart_file(format, root, depth, employees).await?.into_response()),
+        }
+    }
+
+    // 合并部门，preview=true 时只返回将要移动的下级部门与员工
+    pub async fn merge(
+        Extension(service): Extension<Arc<DepartmentService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqMerge>>,
+    ) -> Result<ApiOK<RespMerge>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.merge(req, identity.id()).await
+    }
+}
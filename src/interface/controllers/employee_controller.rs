@@ -1,14 +1,19 @@
 use std::sync::Arc;
 use axum::{
-    extract::{Path, Query},
+    extract::{Multipart, Path, Query},
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use crate::application::services::employee_service::EmployeeService;
 use crate::common::result::{
     rejection::IRejection,
-    response::{ApiErr, ApiOK, Result},
+    response::{ApiErr, ApiFileStream, ApiOK, Result},
 };
-use crate::application::dto::employee_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelectOption};
+use crate::common::export::ExportFormat;
+use crate::application::dto::employee_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelectOption, RespAvatar,
+    RespProfile, ReqProfile, RespSearch,
+    ReqOffboard, RespOffboard, RespHistory, RespHistoryMember};
+use bytes::Bytes;
 use crate::infrastructure::security::identity::Identity;
 use axum_extra::extract::WithRejection;
 use validator::Validate;
@@ -38,7 +43,7 @@ impl EmployeeController {
         if let Err(e) = req.validate() {
             return Err(ApiErr::ErrParams(Some(e.to_string())));
         }
-        service.create(req).await
+        service.create(req, identity.id()).await
     }
     
     pub async fn info(
@@ -59,6 +64,15 @@ impl EmployeeController {
     
         service.list(query).await
     }
+
+    // 模糊搜索，支持拼音全拼与首字母，参数 keyword、limit
+    pub async fn search(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiOK<Vec<RespSearch>>> {
+        service.search(query).await
+    }
     
     
     pub async fn update(
@@ -69,7 +83,7 @@ impl EmployeeController {
         if let Err(e) = req.validate() {
             return Err(ApiErr::ErrParams(Some(e.to_string())));
         }
-        service.update(req).await
+        service.update(req, identity.id()).await
     }
     
     
@@ -90,12 +104,16 @@ impl EmployeeController {
         service.reset_password(employee_id).await
     }
     
+    // 调整部门，可选参数 effective_date（生效日期）、reason（调动原因）
     pub async fn change_department(
         Extension(service): Extension<Arc<EmployeeService>>,
         Extension(identity): Extension<Identity>,
         Path((employee_id, department_id)): Path<(Vec<i64>, i64)>,
+        Query(mut query): Query<HashMap<String, String>>,
     )-> Result<ApiOK<()>> {
-        service.change_department(employee_id, department_id).await
+        let effective_date = query.remove("effective_date");
+        let reason = query.remove("reason");
+        service.change_department(employee_id, department_id, identity.id(), effective_date, reason).await
     }
     
     pub async fn employee_select_list(
@@ -104,6 +122,175 @@ impl EmployeeController {
     )-> Result<ApiOK<Vec<RespSelectOption>>> {
         service.employee_select_list().await
     }
-}
 
+    // 删除员工（逻辑删除）
+    pub async fn delete(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Path(employee_id): Path<i64>,
+    )-> Result<ApiOK<()>> {
+        service.delete(employee_id, identity.id()).await
+    }
+
+    // 办理离职，返回本次变更明细
+    pub async fn offboard(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqOffboard>>,
+    ) -> Result<ApiOK<RespOffboard>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.offboard(req, identity.id()).await
+    }
+
+    // 任职履历
+    pub async fn history(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Path(employee_id): Path<i64>,
+    ) -> Result<ApiOK<Vec<RespHistory>>> {
+        service.history(employee_id).await
+    }
+
+    // 某日在部门任职的员工，参数 department_id、date，include_sub=1 时包含下级部门
+    pub async fn history_members(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiOK<Vec<RespHistoryMember>>> {
+        let department_id = query
+            .get("department_id")
+            .and_then(|v| v.parse::<i64>().ok())
+            .ok_or(ApiErr::ErrParams(Some("部门ID必填".to_string())))?;
+        let date = query.get("date").ok_or(ApiErr::ErrParams(Some("日期必填".to_string())))?;
+        let include_sub = query.get("include_sub").is_some_and(|v| v == "1");
+        service.history_members(department_id, date, include_sub).await
+    }
+
+    // 最近一次离职记录
+    pub async fn offboard_info(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Path(employee_id): Path<i64>,
+    ) -> Result<ApiOK<RespOffboard>> {
+        service.offboard_info(employee_id).await
+    }
+
+    // 恢复已删除员工，可通过 department_id 重新指定部门
+    pub async fn restore(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Path(employee_id): Path<i64>,
+        Query(query): Query<HashMap<String, String>>,
+    )-> Result<ApiOK<()>> {
+        let department_id = match query.get("department_id") {
+            Some(v) => Some(v.parse::<i64>().map_err(|_| ApiErr::ErrParams(Some("部门ID格式错误".to_string())))?),
+            None => None,
+        };
+        service.restore(employee_id, department_id, identity.id()).await
+    }
+
+    // 已删除员工列表
+    pub async fn deleted_list(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiOK<RespList>> {
+        service.deleted_list(query).await
+    }
+
+    // 批量导入员工，上传字段 file（csv/xlsx）
+    // dry_run=1 只校验不写入；strict=1 任一行有误则全部不写入；report=csv|xlsx 时返回错误报告文件
+    pub async fn import(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+        mut multipart: Multipart,
+    ) -> Result<Response> {
+        let flag = |key: &str| query.get(key).is_some_and(|v| v == "1" || v == "true");
+        let report_format = match query.get("report") {
+            Some(v) => Some(ExportFormat::parse(v).map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?),
+            None => None,
+        };
+
+        let (filename, data) = read_file(&mut multipart).await?;
+        let format = ExportFormat::parse(filename.rsplit('.').next().unwrap_or_default())
+            .map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?;
+
+        let report = service.import(format, &data, flag("dry_run"), flag("strict"), identity.id()).await?;
+        match report_format {
+            Some(format) => Ok(service.import_error_file(&report, format)?.into_response()),
+            None => Ok(ApiOK(Some(report)).into_response()),
+        }
+    }
+
+    // 按列表查询条件导出员工，format=csv|xlsx，默认 xlsx
+    pub async fn export(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiFileStream> {
+        let format = ExportFormat::parse(query.get("format").map(String::as_str).unwrap_or("xlsx"))
+            .map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?;
+        service.export(query, format).await
+    }
+
+    // 上传员工头像，上传字段 file
+    pub async fn upload_avatar(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        Path(employee_id): Path<i64>,
+        mut multipart: Multipart,
+    ) -> Result<ApiOK<RespAvatar>> {
+        let (_, data) = read_file(&mut multipart).await?;
+        service.upload_avatar(employee_id, data).await
+    }
+
+    // 当前登录员工的个人资料
+    pub async fn profile(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+    ) -> Result<ApiOK<RespProfile>> {
+        service.profile(identity.id()).await
+    }
 
+    // 修改个人资料，只能修改头像、邮箱、手机号码和备注
+    pub async fn profile_update(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqProfile>>,
+    ) -> Result<ApiOK<()>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.update_profile(identity.id(), req).await
+    }
+
+    // 上传本人头像
+    pub async fn profile_avatar(
+        Extension(service): Extension<Arc<EmployeeService>>,
+        Extension(identity): Extension<Identity>,
+        mut multipart: Multipart,
+    ) -> Result<ApiOK<RespAvatar>> {
+        let (_, data) = read_file(&mut multipart).await?;
+        service.upload_avatar(identity.id(), data).await
+    }
+}
+
+// 读取上传表单中的 file 字段，返回文件名与内容
+async fn read_file(multipart: &mut Multipart) -> Result<(String, Bytes)> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let filename = field.file_name().unwrap_or_default().to_string();
+        let data = field.bytes().await.map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?;
+        return Ok((filename, data));
+    }
+    Err(ApiErr::ErrParams(Some("请上传文件".to_string())))
+}
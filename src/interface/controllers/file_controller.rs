@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use axum::{
+    extract::Path,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use crate::application::services::file_service::FileService;
+use crate::common::result::response::Result;
+
+// 文件名包含内容摘要，可长期缓存
+const CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub struct FileController;
+
+impl FileController {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // 读取存储中的文件
+    pub async fn serve(
+        Extension(service): Extension<Arc<FileService>>,
+        Path(key): Path<String>,
+        headers: HeaderMap,
+    ) -> Result<Response> {
+        let etag = format!("\"{}\"", key);
+        if headers
+            .get(header::IF_NONE_MATCH)
+            .is_some_and(|v| v.as_bytes() == etag.as_bytes())
+        {
+            return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+        }
+
+        let data = service.get(&key).await?;
+        let content_type = match key.rsplit('.').next() {
+            Some("png") => "image/png",
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            _ => "application/octet-stream",
+        };
+        Ok((
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::CACHE_CONTROL, CACHE_CONTROL.to_string()),
+                (header::ETAG, etag),
+            ],
+            data,
+        )
+            .into_response())
+    }
+}
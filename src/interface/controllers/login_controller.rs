@@ -2,7 +2,7 @@ use std::sync::Arc;
 use axum::{Extension, Json};
 use crate::application::services::login_service::LoginService;
 use crate::common::result::response::{ApiOK, Result};
-use crate::application::dto::login_dto::{ReqLogin, RespLogin};
+use crate::application::dto::login_dto::{ReqLogin, RespLogin, RespCurrentUser};
 use crate::infrastructure::security::identity::Identity;
 use axum_extra::extract::WithRejection;
 use validator::Validate;
@@ -43,5 +43,11 @@ impl LoginController {
         }
         service.logout(identity).await
     }
+
+    pub async fn current_user(
+        Extension(service): Extension<Arc<LoginService>>,
+        Extension(identity): Extension<Identity>) -> Result<ApiOK<RespCurrentUser>> {
+        service.current_user(identity).await
+    }
     
 }
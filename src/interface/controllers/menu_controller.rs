@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use axum::{
+    extract::Query,
+    Extension, Json,
+};
+use axum_extra::extract::WithRejection;
+use crate::application::services::menu_service::MenuService;
+use crate::application::dto::menu_dto::{MenuDocument, RespImportReport};
+use crate::common::result::{
+    rejection::IRejection,
+    response::{ApiOK, Result},
+};
+use crate::infrastructure::security::identity::Identity;
+
+pub struct MenuController;
+
+impl MenuController {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // 导出菜单及角色功能绑定
+    pub async fn export(
+        Extension(service): Extension<Arc<MenuService>>,
+        Extension(identity): Extension<Identity>,
+    ) -> Result<ApiOK<MenuDocument>> {
+        service.export().await
+    }
+
+    // 导入菜单，dry_run=1 时只返回差异不写入
+    pub async fn import(
+        Extension(service): Extension<Arc<MenuService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+        WithRejection(Json(req), _): IRejection<Json<MenuDocument>>,
+    ) -> Result<ApiOK<RespImportReport>> {
+        let dry_run = query.get("dry_run").is_some_and(|v| v == "1" || v == "true");
+        service.import(req, identity.id(), dry_run).await
+    }
+}
@@ -3,3 +3,5 @@ pub mod login_controller;
 pub mod role_controller;
 pub mod position_controller;
 pub mod employee_controller;
+pub mod menu_controller;
+pub mod file_controller;
@@ -73,6 +73,24 @@ impl PositionController {
     
         service.delete(post_id).await
     }
+
+    // 恢复已删除职务
+    pub async fn restore(
+        Extension(service): Extension<Arc<PositionService>>,
+        Extension(identity): Extension<Identity>,
+        Path(post_id): Path<u64>,
+    ) -> Result<ApiOK<()>> {
+        service.restore(post_id).await
+    }
+
+    // 已删除职务列表
+    pub async fn deleted_list(
+        Extension(service): Extension<Arc<PositionService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiOK<RespList>> {
+        service.deleted_list(query).await
+    }
     
     
     pub async fn select_list(
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use axum_extra::extract::WithRejection;
 use validator::Validate;
-use crate::common::tree;
+use crate::common::{tree, export::ExportFormat};
 use std::sync::Arc;
 use crate::application::services::role_service::RoleService;
 use crate::infrastructure::security::identity::Identity;
@@ -11,9 +11,11 @@ use axum::{
 };
 use crate::common::result::{
     rejection::IRejection,
-    response::{ApiErr, ApiOK, Result},
+    response::{ApiErr, ApiFile, ApiOK, Result},
 };
-use crate::application::dto::role_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelect, RespEmpList, RespRoleMenu};
+use crate::application::dto::role_dto::{ReqCreate, UpdateInfo, RespInfo, RespList, RespSelect, RespEmpList, RespRoleMenu, ReqRoleMenu,
+    ReqRoleEmp, ReqEmpRole, RespRoleEmpChange, RespEffectiveMenu,
+    ReqRoleClone, RespRoleCompare};
 
 
 
@@ -104,7 +106,7 @@ impl RoleController {
     pub async fn role_func_list(
         Extension(service): Extension<Arc<RoleService>>,
         Extension(identity): Extension<Identity>,
-    ) -> Result<ApiOK<Vec<tree::TreeNode>>>{
+    ) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>>{
         service.menu_list().await
     }
     
@@ -116,6 +118,122 @@ impl RoleController {
     ) -> Result<ApiOK<Vec<RespRoleMenu>>>{
         service.role_menu(role_id).await
     }
-    
-}
 
+    //功能权限-保存角色的功能权限
+    pub async fn role_func_save(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqRoleMenu>>,
+    ) -> Result<ApiOK<Vec<RespRoleMenu>>>{
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.save_role_menu(req).await
+    }
+
+    // 角色成员-批量添加
+    pub async fn role_emp_add(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqRoleEmp>>,
+    ) -> Result<ApiOK<RespRoleEmpChange>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.add_role_emp(req).await
+    }
+
+    // 角色成员-批量设置有效期
+    pub async fn role_emp_validity(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqRoleEmp>>,
+    ) -> Result<ApiOK<RespRoleEmpChange>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.set_role_emp_validity(req).await
+    }
+
+    // 角色成员-批量移除
+    pub async fn role_emp_remove(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqRoleEmp>>,
+    ) -> Result<ApiOK<RespRoleEmpChange>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.remove_role_emp(req).await
+    }
+
+    // 根据员工Id查询员工拥有的角色
+    pub async fn emp_roles(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        Path(employee_id): Path<i64>,
+    ) -> Result<ApiOK<Vec<RespSelect>>> {
+        service.emp_roles(employee_id).await
+    }
+
+    // 整体替换员工的角色
+    pub async fn emp_roles_save(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqEmpRole>>,
+    ) -> Result<ApiOK<Vec<RespSelect>>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.save_emp_roles(req).await
+    }
+
+    // 角色树
+    pub async fn role_tree(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+    ) -> Result<ApiOK<Vec<tree::TreeNode<tree::Item>>>> {
+        service.role_tree().await
+    }
+
+    //功能权限-根据角色Id查询包含继承在内的有效功能权限
+    pub async fn role_func_effective(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        Path(role_id): Path<i64>,
+    ) -> Result<ApiOK<Vec<RespEffectiveMenu>>> {
+        service.role_menu_effective(role_id).await
+    }
+
+    // 复制角色
+    pub async fn role_clone(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        WithRejection(Json(req), _): IRejection<Json<ReqRoleClone>>,
+    ) -> Result<ApiOK<RespInfo>> {
+        if let Err(e) = req.validate() {
+            return Err(ApiErr::ErrParams(Some(e.to_string())));
+        }
+        service.clone_role(req).await
+    }
+
+    // 对比两个角色的功能权限
+    pub async fn role_compare(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        Path((role_a, role_b)): Path<(i64, i64)>,
+    ) -> Result<ApiOK<RespRoleCompare>> {
+        service.compare_roles(role_a, role_b).await
+    }
+
+    // 导出角色权限矩阵，format=csv|xlsx，默认 xlsx
+    pub async fn role_matrix_export(
+        Extension(service): Extension<Arc<RoleService>>,
+        Extension(identity): Extension<Identity>,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> Result<ApiFile> {
+        let format = ExportFormat::parse(query.get("format").map(String::as_str).unwrap_or("xlsx"))
+            .map_err(|e| ApiErr::ErrParams(Some(e.to_string())))?;
+        service.export_matrix(format, query.get("sheet").map(String::as_str)).await
+    }
+}
@@ -4,13 +4,13 @@ pub mod middleware;
 
 use anyhow::Result;
 use anyhow::anyhow;
-use sea_orm::EntityTrait;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use crate::infrastructure::security::identity::Identity;
 
 
 
 
-use crate::domain::entities::prelude::TEmployee;
+use crate::domain::entities::{t_employee, prelude::TEmployee};
 use crate::infrastructure::persistence::database as db;
 
 
@@ -22,7 +22,12 @@ pub async fn auth_check(identity: &Identity) -> Result<()> {
         return Err(anyhow!("未授权，请先登录"));
     }
 
-    match TEmployee::find_by_id(identity.id()).one(db::conn()).await {
+    // 已删除的员工视为不存在
+    match TEmployee::find_by_id(identity.id())
+        .filter(t_employee::Column::DeletedFlag.eq(0))
+        .one(db::conn())
+        .await
+    {
         Ok(Some(employee)) => {
             if employee.login_token.is_empty() || !identity.match_token(&employee.login_token) {
                 tracing::warn!("Invalid token for user_id: {}", identity.id());
@@ -1,5 +1,6 @@
 use axum::{
     body::Body,
+    extract::DefaultBodyLimit,
     http::Request,
     routing::{get, post},
     Router,
@@ -13,6 +14,8 @@ use crate::application::services::login_service::LoginService;
 use crate::application::services::role_service::RoleService;
 use crate::application::services::position_service::PositionService;
 use crate::application::services::employee_service::EmployeeService;
+use crate::application::services::menu_service::MenuService;
+use crate::application::services::file_service::FileService;
 use crate::interface::middleware::auth;
 use crate::interface::middleware::log;
 use crate::interface::middleware::identity;
@@ -24,6 +27,8 @@ use crate::interface::controllers::login_controller::LoginController as login;
 use crate::interface::controllers::role_controller::RoleController as role;
 use crate::interface::controllers::position_controller::PositionController as position;
 use crate::interface::controllers::employee_controller::EmployeeController as employee;
+use crate::interface::controllers::menu_controller::MenuController as menu;
+use crate::interface::controllers::file_controller::FileController as file;
 
 pub fn init() -> Router {
     
@@ -33,17 +38,21 @@ pub fn init() -> Router {
     let role_service = Arc::new(RoleService::new());
     let position_service = Arc::new(PositionService::new());
     let employee_service = Arc::new(EmployeeService::new());
+    let menu_service = Arc::new(MenuService::new());
+    let file_service = Arc::new(FileService::new());
 
 
      // 开放
      let open = Router::new().route("/login", post(login::login))
      .route("/logout", post(login::logout))
-     .layer(Extension(login_service));
+     .layer(Extension(login_service.clone()))
+     // 上传文件（头像等）
+     .route("/files/*key", get(file::serve).layer(Extension(file_service)));
 
 
     // 需要鉴权的路由
     let auth = Router::new()
-        .nest("/api", api_routes(department_service, role_service, position_service, employee_service))
+        .nest("/api", api_routes(department_service, role_service, position_service, employee_service, login_service.clone(), menu_service))
         .layer(axum::middleware::from_fn(auth::handle));
 
         Router::new()
@@ -74,16 +83,26 @@ fn api_routes(
     service: Arc<DepartmentService>, 
     role_service: Arc<RoleService>,
     position_service: Arc<PositionService>,
-    employee_service: Arc<EmployeeService>) -> Router {
+    employee_service: Arc<EmployeeService>,
+    login_service: Arc<LoginService>,
+    menu_service: Arc<MenuService>) -> Router {
     Router::new()
         // 部门相关路由
-        .nest("/departments", department_routes(service))
+        .nest("/departments", department_routes(service.clone()))
+        // 我的团队
+        .nest("/team", team_routes(service))
         // 角色相关路由
         .nest("/roles", role_routes(role_service))
         // 职位相关路由
         .nest("/positions", position_routes(position_service))
         // 员工相关路由
-        .nest("/employees", employee_routes(employee_service))
+        .nest("/employees", employee_routes(employee_service.clone()))
+        // 个人资料
+        .nest("/profile", profile_routes(employee_service))
+        // 菜单相关路由
+        .nest("/menus", menu_routes(menu_service))
+        // 当前登录员工
+        .route("/current_user", get(login::current_user).layer(Extension(login_service)))
 }
 
 // 部门路由
@@ -93,6 +112,22 @@ fn department_routes(service: Arc<DepartmentService>) -> Router {
         .route("/departments/:department_id", get(department::info).delete(department::delete))
         .route("/departments/update", post(department::update))
         .route("/departments/select_list", get(department::select_list))
+        .route("/departments/search", get(department::search))
+        .route("/departments/move", post(department::move_department))
+        .route("/departments/reorder", post(department::reorder))
+        .route("/departments/ancestors/:department_id", get(department::ancestors))
+        .route("/departments/descendants/:department_id", get(department::descendants))
+        .route("/departments/org_chart", get(department::org_chart))
+        .route("/departments/merge", post(department::merge))
+        .layer(Extension(service))
+}
+
+// 我的团队路由：负责的部门与汇报关系
+fn team_routes(service: Arc<DepartmentService>) -> Router {
+    Router::new()
+        .route("/departments", get(department::managed))
+        .route("/reports", get(department::direct_reports))
+        .route("/tree", get(department::reporting_tree))
         .layer(Extension(service))
 }
 
@@ -106,6 +141,17 @@ fn role_routes(service: Arc<RoleService>) -> Router {
     .route("/roles/role_emp_list", get(role::role_emp_list))
     .route("/roles/role_func_list", get(role::role_func_list))
     .route("/roles/role_func_id", get(role::role_func_id))
+    .route("/roles/role_func_save", post(role::role_func_save))
+    .route("/roles/role_emp_add", post(role::role_emp_add))
+    .route("/roles/role_emp_remove", post(role::role_emp_remove))
+    .route("/roles/role_emp_validity", post(role::role_emp_validity))
+    .route("/roles/emp_roles/:employee_id", get(role::emp_roles))
+    .route("/roles/emp_roles_save", post(role::emp_roles_save))
+    .route("/roles/role_tree", get(role::role_tree))
+    .route("/roles/role_func_effective/:role_id", get(role::role_func_effective))
+    .route("/roles/role_clone", post(role::role_clone))
+    .route("/roles/role_compare/:role_a/:role_b", get(role::role_compare))
+    .route("/roles/role_matrix_export", get(role::role_matrix_export))
     .layer(Extension(service))
 }
 
@@ -116,6 +162,8 @@ fn position_routes(service: Arc<PositionService>) -> Router {
     .route("/positions/:post_id", get(position::info).delete(position::delete))
     .route("/positions/update", post(position::update))
     .route("/positions/select_list", get(position::select_list))
+    .route("/positions/restore/:post_id", get(position::restore))
+    .route("/positions/deleted_list", get(position::deleted_list))
     .layer(Extension(service))
 }
 
@@ -123,12 +171,44 @@ fn position_routes(service: Arc<PositionService>) -> Router {
 fn employee_routes(service: Arc<EmployeeService>) -> Router {
     Router::new()
     .route("/employees", get(employee::list).post(employee::create))
-    .route("/employees/:employee_id", get(employee::info))
+    .route("/employees/:employee_id", get(employee::info).delete(employee::delete))
     .route("/employees/update", post(employee::update))
     .route("/employees/disabled_flag/:employee_id/:disabled_flag", get(employee::disabled_flag))
     .route("/employees/reset_password/:employee_id", get(employee::reset_password))
     .route("/employees/change_department/:employee_ids/:department_id", get(employee::change_department))
     .route("/employees/employee_select_list", get(employee::employee_select_list))
+    .route("/employees/search", get(employee::search))
+    .route("/employees/restore/:employee_id", get(employee::restore))
+    .route("/employees/deleted_list", get(employee::deleted_list))
+    .route("/employees/offboard", post(employee::offboard))
+    .route("/employees/offboard/:employee_id", get(employee::offboard_info))
+    .route("/employees/history/members", get(employee::history_members))
+    .route("/employees/history/:employee_id", get(employee::history))
+    .route("/employees/import", post(employee::import))
+    .route("/employees/export", get(employee::export))
+    .route(
+        "/employees/avatar/:employee_id",
+        // 预留 multipart 表单的额外开销
+        post(employee::upload_avatar).layer(DefaultBodyLimit::max(EmployeeService::avatar_max_size() + 64 * 1024)),
+    )
     .layer(Extension(service))
 }
 
+// 个人资料路由
+fn profile_routes(service: Arc<EmployeeService>) -> Router {
+    Router::new()
+    .route("/", get(employee::profile).put(employee::profile_update))
+    .route(
+        "/avatar",
+        post(employee::profile_avatar).layer(DefaultBodyLimit::max(EmployeeService::avatar_max_size() + 64 * 1024)),
+    )
+    .layer(Extension(service))
+}
+
+// 菜单路由
+fn menu_routes(service: Arc<MenuService>) -> Router {
+    Router::new()
+    .route("/menus/export", get(menu::export))
+    .route("/menus/import", post(menu::import))
+    .layer(Extension(service))
+}
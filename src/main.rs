@@ -8,7 +8,7 @@ mod domain;
 use anyhow::{Context, Result};
 use tracing::info;
 
-use crate::cli::{Cli, Command, hello, serve};
+use crate::cli::{Cli, Command, hello, serve, menu, role, employee, search, department};
 use clap::Parser;
 use crate::common::{config, logger};
 use crate::infrastructure::persistence::database;
@@ -32,6 +32,11 @@ async fn main() -> Result<()> {
         match v {
             Command::Hello { name } => hello::execute(name).await?,
             Command::Serve => serve::execute("127.0.0.1".into(), 8080).await?,
+            Command::Menu { action } => menu::execute(action).await?,
+            Command::Role { action } => role::execute(action).await?,
+            Command::Employee { action } => employee::execute(action).await?,
+            Command::Department { action } => department::execute(action).await?,
+            Command::Search { action } => search::execute(action).await?,
         }
     }
     Ok(())